@@ -0,0 +1,129 @@
+use nalgebra_glm::Vec3;
+use crate::camera::Camera;
+use crate::debug_view::DebugView;
+use crate::framebuffer::{pack_pixel, unpack_pixel, Framebuffer};
+use crate::light::Light;
+use crate::ray_intersect::RayIntersect;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+// Acumulación progresiva: mientras la vista no cambia, cada cuadro se renderiza con un
+// desplazamiento subpixel distinto y se promedia con los anteriores en un buffer flotante.
+pub struct Accumulator {
+    pub enabled: bool,
+    pub max_samples: u32, // Al llegar aquí la imagen se considera convergida y se deja de renderizar
+    pub samples: u32,     // Cuadros promediados desde el último reinicio
+    width: usize,
+    height: usize,
+    sum: Vec<Vec3>,       // Suma de los colores de cada pixel, en [0, 1]
+    signature: Option<u64>,
+}
+
+impl Accumulator {
+    pub fn new(max_samples: u32) -> Self {
+        Accumulator {
+            enabled: true,
+            max_samples: max_samples.max(1),
+            samples: 0,
+            width: 0,
+            height: 0,
+            sum: Vec::new(),
+            signature: None,
+        }
+    }
+
+    // Descartar lo acumulado
+    pub fn reset(&mut self) {
+        self.samples = 0;
+        self.sum.iter_mut().for_each(|value| *value = Vec3::zeros());
+    }
+
+    // La vista no cambió desde el cuadro anterior
+    pub fn is_still(&self, signature: u64) -> bool {
+        self.signature == Some(signature)
+    }
+
+    // Preparar un cuadro: reinicia si cambió el tamaño o la firma de la vista
+    pub fn begin(&mut self, width: usize, height: usize, signature: u64) {
+        if !self.enabled || width != self.width || height != self.height || self.signature != Some(signature) {
+            self.width = width;
+            self.height = height;
+            self.sum = vec![Vec3::zeros(); width * height];
+            self.samples = 0;
+        }
+        self.signature = Some(signature);
+    }
+
+    pub fn is_converged(&self) -> bool {
+        self.enabled && self.samples >= self.max_samples
+    }
+
+    // Desplazamiento subpixel del próximo cuadro (secuencia de Halton 2, 3); el primero va sin desplazar
+    pub fn jitter(&self) -> (f32, f32) {
+        if !self.enabled || self.samples == 0 {
+            return (0.0, 0.0);
+        }
+        (halton(self.samples, 2) - 0.5, halton(self.samples, 3) - 0.5)
+    }
+
+    // Sumar el cuadro recién renderizado y dejar el promedio en el framebuffer
+    pub fn add(&mut self, framebuffer: &mut Framebuffer) {
+        if !self.enabled {
+            return;
+        }
+        for (sum, &pixel) in self.sum.iter_mut().zip(framebuffer.buffer.iter()) {
+            *sum += unpack_pixel(pixel);
+        }
+        self.samples += 1;
+        self.resolve(framebuffer);
+    }
+
+    // Escribir el promedio acumulado en el framebuffer
+    pub fn resolve(&self, framebuffer: &mut Framebuffer) {
+        if self.samples == 0 {
+            return;
+        }
+        let scale = 1.0 / self.samples as f32;
+        for (pixel, sum) in framebuffer.buffer.iter_mut().zip(self.sum.iter()) {
+            *pixel = pack_pixel(sum * scale);
+        }
+    }
+}
+
+// Firma de todo lo que afecta a la imagen: cámara, luz, posiciones de los objetos y el tiempo de
+// la animación (con pistas activas, el giro, la escala o el material pueden cambiar sin moverlos)
+pub fn view_signature(camera: &Camera, debug_view: DebugView, light: &Light, objects: &[Box<dyn RayIntersect>], time: f32) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let mut hash_vec = |v: &Vec3| v.iter().for_each(|c| c.to_bits().hash(&mut hasher));
+
+    hash_vec(&camera.eye);
+    hash_vec(&camera.center);
+    hash_vec(&camera.up);
+    hash_vec(&light.position);
+    for object in objects {
+        hash_vec(&object.position());
+    }
+
+    [
+        camera.fov, camera.aspect_ratio, camera.near_plane, camera.far_plane, camera.aperture,
+        camera.focus_distance, camera.ortho_height, camera.fisheye_fov,
+        camera.interocular_distance, camera.convergence_distance, light.intensity, time,
+    ].iter().for_each(|value| value.to_bits().hash(&mut hasher));
+    std::mem::discriminant(&camera.projection).hash(&mut hasher);
+    std::mem::discriminant(&camera.stereo).hash(&mut hasher);
+    std::mem::discriminant(&debug_view).hash(&mut hasher);
+    objects.len().hash(&mut hasher);
+    hasher.finish()
+}
+
+// Elemento `index` de la secuencia de Halton en la base dada, en [0, 1)
+fn halton(mut index: u32, base: u32) -> f32 {
+    let mut result = 0.0;
+    let mut fraction = 1.0 / base as f32;
+    while index > 0 {
+        result += (index % base) as f32 * fraction;
+        index /= base;
+        fraction /= base as f32;
+    }
+    result
+}
@@ -0,0 +1,216 @@
+use nalgebra_glm::Vec3;
+use crate::cube::Cube;
+use std::f32::consts::TAU;
+use std::sync::Arc;
+
+// Forma de la transición entre dos keyframes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    EaseIn,    // Arranca lento
+    EaseOut,   // Frena al llegar
+    EaseInOut, // Arranca lento y frena al llegar
+    Step,      // Salta al valor siguiente al llegar a su keyframe
+}
+
+impl Easing {
+    // Fracción del recorrido ya hecha para una fracción de tiempo `x` en [0, 1]
+    pub fn apply(&self, x: f32) -> f32 {
+        match self {
+            Easing::Linear => x,
+            Easing::EaseIn => x * x,
+            Easing::EaseOut => x * (2.0 - x),
+            Easing::EaseInOut => x * x * (3.0 - 2.0 * x),
+            Easing::Step => 0.0,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Easing::Linear => "linear",
+            Easing::EaseIn => "ease_in",
+            Easing::EaseOut => "ease_out",
+            Easing::EaseInOut => "ease_in_out",
+            Easing::Step => "step",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Easing> {
+        [Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut, Easing::Step]
+            .into_iter()
+            .find(|easing| easing.name() == name)
+    }
+}
+
+// Valor de una pista en función del tiempo
+#[derive(Debug, Clone)]
+pub enum Curve {
+    // amplitude * sin(2π (t / period + phase + stagger * k)), donde k es el orden del objeto
+    // dentro del grupo animado; phase y stagger se miden en vueltas
+    Sine { amplitude: f32, period: f32, phase: f32, stagger: f32 },
+    // Pares (tiempo, valor) ordenados por tiempo; fuera del rango se mantiene el extremo,
+    // salvo con `looping`, que repite el tramo [0, último tiempo]
+    Keyframes { keys: Vec<(f32, f32)>, easing: Easing, looping: bool },
+}
+
+impl Curve {
+    // Valor en el tiempo `t` para el objeto `k` de su grupo
+    pub fn evaluate(&self, t: f32, k: usize) -> f32 {
+        match self {
+            Curve::Sine { amplitude, period, phase, stagger } => {
+                amplitude * (TAU * (t / period + phase + stagger * k as f32)).sin()
+            }
+            Curve::Keyframes { keys, easing, looping } => {
+                let (first, last) = match (keys.first(), keys.last()) {
+                    (Some(first), Some(last)) => (*first, *last),
+                    _ => return 0.0,
+                };
+                let t = if *looping && last.0 > 0.0 { t.rem_euclid(last.0) } else { t };
+                if t <= first.0 {
+                    return first.1;
+                }
+                match keys.windows(2).find(|pair| t < pair[1].0) {
+                    Some(pair) => {
+                        let ((t0, v0), (t1, v1)) = (pair[0], pair[1]);
+                        v0 + (v1 - v0) * easing.apply((t - t0) / (t1 - t0))
+                    }
+                    None => last.1,
+                }
+            }
+        }
+    }
+}
+
+// Propiedad animada. Todas son desplazamientos respecto a la pose de reposo.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Channel {
+    X,
+    Y,
+    Z,
+    Rotation,     // Giro alrededor del eje vertical, en grados
+    Scale,        // Fracción del tamaño que se agrega (0.2 = 20% más grande)
+    Transparency, // Se suma a la transparencia del material, recortada a [0, 1]
+    Specular,     // Se suma al exponente especular del material
+}
+
+impl Channel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Channel::X => "x",
+            Channel::Y => "y",
+            Channel::Z => "z",
+            Channel::Rotation => "rotation",
+            Channel::Scale => "scale",
+            Channel::Transparency => "transparency",
+            Channel::Specular => "specular",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Channel> {
+        [
+            Channel::X, Channel::Y, Channel::Z, Channel::Rotation,
+            Channel::Scale, Channel::Transparency, Channel::Specular,
+        ]
+        .into_iter()
+        .find(|channel| channel.name() == name)
+    }
+}
+
+// A qué bloques afecta una pista
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    Block(usize),     // Un bloque por su índice (el mismo que muestra el inspector)
+    Material(String), // Todos los bloques de un material, en orden
+}
+
+#[derive(Debug, Clone)]
+pub struct Track {
+    pub target: Target,
+    pub channel: Channel,
+    pub curve: Curve,
+}
+
+// Conjunto de pistas que posan los bloques a partir de su pose de reposo
+#[derive(Debug, Clone, Default)]
+pub struct Animation {
+    pub tracks: Vec<Track>,
+}
+
+impl Animation {
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    // Se quitó el bloque `index` de la lista: sus pistas se descartan y las de los bloques
+    // siguientes bajan un índice, así cada pista sigue animando el mismo bloque
+    pub fn block_removed(&mut self, index: usize) {
+        self.tracks.retain(|track| track.target != Target::Block(index));
+        for track in &mut self.tracks {
+            if let Target::Block(block) = &mut track.target {
+                if *block > index {
+                    *block -= 1;
+                }
+            }
+        }
+    }
+
+    // Pose de los bloques en el tiempo `t`. Siempre se parte de `rest`, así que evaluar el mismo
+    // instante da el mismo resultado sin importar cuántos cuadros pasaron.
+    pub fn pose(&self, rest: &[Cube], t: f32) -> Vec<Cube> {
+        let mut posed = rest.to_vec();
+        if self.is_empty() {
+            return posed;
+        }
+
+        // Desplazamientos acumulados de cada bloque: varias pistas sobre el mismo canal se suman
+        let mut offsets = vec![Offsets::default(); rest.len()];
+        for track in &self.tracks {
+            let mut k = 0;
+            for (index, cube) in rest.iter().enumerate() {
+                let affected = match &track.target {
+                    Target::Block(block) => *block == index,
+                    Target::Material(name) => cube.material.name == name,
+                };
+                if !affected {
+                    continue;
+                }
+                let value = track.curve.evaluate(t, k);
+                k += 1;
+
+                let offset = &mut offsets[index];
+                match track.channel {
+                    Channel::X => offset.position.x += value,
+                    Channel::Y => offset.position.y += value,
+                    Channel::Z => offset.position.z += value,
+                    Channel::Rotation => offset.rotation += value.to_radians(),
+                    Channel::Scale => offset.scale += value,
+                    Channel::Transparency => offset.transparency = Some(offset.transparency.unwrap_or(0.0) + value),
+                    Channel::Specular => offset.specular = Some(offset.specular.unwrap_or(0.0) + value),
+                }
+            }
+        }
+
+        for (cube, offset) in posed.iter_mut().zip(offsets) {
+            cube.center += offset.position;
+            cube.rotation += offset.rotation;
+            cube.size *= (1.0 + offset.scale).max(0.0);
+            if offset.transparency.is_some() || offset.specular.is_some() {
+                let mut material = (*cube.material).clone();
+                material.transparency = (material.transparency + offset.transparency.unwrap_or(0.0)).clamp(0.0, 1.0);
+                material.specular = (material.specular + offset.specular.unwrap_or(0.0)).max(0.0);
+                cube.material = Arc::new(material);
+            }
+        }
+        posed
+    }
+}
+
+// Suma de lo que las pistas le aplican a un bloque; los del material solo si alguna pista los toca
+#[derive(Debug, Clone, Default)]
+struct Offsets {
+    position: Vec3,
+    rotation: f32,
+    scale: f32,
+    transparency: Option<f32>,
+    specular: Option<f32>,
+}
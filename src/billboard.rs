@@ -0,0 +1,59 @@
+use nalgebra_glm::Vec3;
+use crate::ray_intersect::{RayIntersect, Intersect};
+use crate::material::Material;
+use std::sync::Arc;
+
+// Cuadrado plano orientado hacia un punto de vista (la cámara), con su borde superior hacia +y
+#[derive(Clone)]
+pub struct Billboard {
+    pub center: Vec3,
+    pub size: f32,     // Largo del lado
+    pub normal: Vec3,  // Hacia la cámara
+    pub right: Vec3,   // Eje horizontal del cuadrado
+    pub up: Vec3,      // Eje vertical del cuadrado
+    pub material: Arc<Material>,
+}
+
+impl Billboard {
+    // Cuadrado en `center` que mira hacia `eye`
+    pub fn facing(center: Vec3, size: f32, eye: &Vec3, material: Arc<Material>) -> Self {
+        let to_eye = eye - center;
+        let normal = if to_eye.magnitude() > 1e-6 { to_eye.normalize() } else { Vec3::z() };
+        // Mirando justo hacia arriba o abajo el eje vertical no sirve de referencia
+        let reference = if normal.y.abs() > 0.99 { Vec3::z() } else { Vec3::y() };
+        let right = reference.cross(&normal).normalize();
+        let up = normal.cross(&right);
+        Billboard { center, size, normal, right, up, material }
+    }
+}
+
+impl RayIntersect for Billboard {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        let denominator = self.normal.dot(ray_direction);
+        if denominator.abs() < 1e-6 {
+            return Intersect::empty();
+        }
+        let distance = (self.center - ray_origin).dot(&self.normal) / denominator;
+        if distance <= 0.0 {
+            return Intersect::empty();
+        }
+
+        let point = ray_origin + ray_direction * distance;
+        let local = point - self.center;
+        let half = self.size / 2.0;
+        let (x, y) = (local.dot(&self.right), local.dot(&self.up));
+        if x.abs() > half || y.abs() > half {
+            return Intersect::empty();
+        }
+
+        // La cara que se ve es siempre la del lado del rayo
+        let normal = if denominator > 0.0 { -self.normal } else { self.normal };
+        let u = (x + half) / self.size;
+        let v = (half - y) / self.size;
+        Intersect::new(point, normal, distance, (*self.material).clone(), u, v)
+    }
+
+    fn position(&self) -> Vec3 {
+        self.center
+    }
+}
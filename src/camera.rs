@@ -1,14 +1,72 @@
-#[allow(dead_code)]
-use nalgebra_glm::{Vec3, Mat4, look_at, perspective};
+use nalgebra_glm::{Vec3, Mat4, look_at, ortho, perspective};
 
+// Modo de control de la cámara
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CameraMode {
+    Orbit, // Gira alrededor de `center`
+    Fly,   // Vuelo libre en primera persona
+}
+
+// Proyección usada para generar los rayos primarios
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    Perspective,     // Rayos desde `eye` según `fov`
+    Orthographic,    // Rayos paralelos repartidos sobre el plano de imagen
+    Equirectangular, // Panorama de 360° x 180° (longitud/latitud)
+    Fisheye,         // Ojo de pez equidistante de `fisheye_fov` grados
+}
+
+// Ángulos predefinidos para vistas ortográficas de la maqueta
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ViewPreset {
+    Isometric, // Elevación de ~35.26° (los tres ejes con el mismo escorzo)
+    Dimetric,  // Elevación de ~26.57° (proporción de pixeles 2:1)
+}
+
+impl ViewPreset {
+    // Elevación sobre el plano XZ, en radianes
+    pub fn elevation(&self) -> f32 {
+        match self {
+            ViewPreset::Isometric => (1.0 / 2.0f32.sqrt()).atan(),
+            ViewPreset::Dimetric => 0.5f32.atan(),
+        }
+    }
+}
+
+// Modo de salida estereoscópica
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StereoMode {
+    Mono,       // Una sola vista
+    Anaglyph,   // Rojo (ojo izquierdo) / cian (ojo derecho) en la misma imagen
+    SideBySide, // Ojo izquierdo a la izquierda, derecho a la derecha
+    OverUnder,  // Ojo izquierdo arriba, derecho abajo
+}
+
+// Ojo para el que se genera una vista estereoscópica
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Eye {
+    Left,
+    Right,
+}
+
+#[derive(Clone)]
 pub struct Camera {
     pub eye: Vec3,    // Posición de la cámara en el espacio mundial
     pub center: Vec3, // Punto que la cámara está mirando
     pub up: Vec3,     // Vector "arriba" de la cámara
-    pub fov: f32,     // Campo de visión en radianes
+    pub fov: f32,     // Campo de visión vertical en grados
     pub aspect_ratio: f32,  // Relación de aspecto (ancho/alto)
     pub near_plane: f32,    // Plano cercano de recorte
     pub far_plane: f32,     // Plano lejano de recorte
+    pub aperture: f32,       // Radio de la apertura del lente (0 = cámara estenopeica)
+    pub focus_distance: f32, // Distancia al plano de enfoque, medida sobre el eje de vista
+    pub mode: CameraMode,    // Modo de control activo
+    pub projection: Projection, // Perspectiva u ortográfica
+    pub ortho_height: f32,      // Alto visible (en unidades del mundo) de la vista ortográfica
+    pub fisheye_fov: f32,       // Campo de visión del ojo de pez, en grados
+    pub stereo: StereoMode,          // Salida mono o estereoscópica
+    pub interocular_distance: f32,   // Separación entre los ojos, en unidades del mundo
+    pub convergence_distance: f32,   // Distancia a la que ambos ojos convergen (paralaje cero)
 }
 
 impl Camera {
@@ -21,7 +79,16 @@ impl Camera {
             fov, 
             aspect_ratio, 
             near_plane, 
-            far_plane 
+            far_plane,
+            aperture: 0.0,
+            focus_distance: (center - eye).magnitude(),
+            mode: CameraMode::Orbit,
+            projection: Projection::Perspective,
+            ortho_height: 6.0,
+            fisheye_fov: 180.0,
+            stereo: StereoMode::Mono,
+            interocular_distance: 0.1,
+            convergence_distance: (center - eye).magnitude(),
         }
     }
 
@@ -37,7 +104,17 @@ impl Camera {
 
     // Método para obtener la matriz de proyección
     pub fn get_projection_matrix(&self) -> Mat4 {
-        perspective(self.aspect_ratio, self.fov, self.near_plane, self.far_plane)
+        match self.projection {
+            // Las proyecciones panorámicas no tienen matriz; se usa la perspectiva como aproximación
+            Projection::Perspective | Projection::Equirectangular | Projection::Fisheye => {
+                perspective(self.aspect_ratio, self.fov.to_radians(), self.near_plane, self.far_plane)
+            }
+            Projection::Orthographic => {
+                let half_height = self.ortho_height / 2.0;
+                let half_width = half_height * self.aspect_ratio;
+                ortho(-half_width, half_width, -half_height, half_height, self.near_plane, self.far_plane)
+            }
+        }
     }
 
     // Método para obtener la matriz combinada de vista/proyección
@@ -45,6 +122,105 @@ impl Camera {
         self.get_projection_matrix() * self.get_view_matrix()
     }
 
+    // Dirección del rayo primario para un punto de pantalla en [-1, 1] x [-1, 1]
+    pub fn ray_direction(&self, screen_x: f32, screen_y: f32, aspect_ratio: f32) -> Vec3 {
+        let scale = (self.fov.to_radians() * 0.5).tan();
+        self.basis_change(&Vec3::new(screen_x * aspect_ratio * scale, screen_y * scale, -1.0))
+    }
+
+    // Rayo primario (origen, dirección) para un punto de pantalla en [-1, 1] x [-1, 1].
+    // Devuelve None para los pixeles que la proyección no cubre (fuera del círculo del ojo de pez).
+    pub fn primary_ray(&self, screen_x: f32, screen_y: f32, aspect_ratio: f32) -> Option<(Vec3, Vec3)> {
+        match self.projection {
+            Projection::Perspective => Some((self.eye, self.ray_direction(screen_x, screen_y, aspect_ratio))),
+            Projection::Orthographic => {
+                // Todos los rayos son paralelos; el origen recorre el plano de imagen
+                let forward = self.view_direction();
+                let right = forward.cross(&self.up).normalize();
+                let up = right.cross(&forward).normalize();
+                let half_height = self.ortho_height / 2.0;
+                let origin = self.eye + right * (screen_x * aspect_ratio * half_height) + up * (screen_y * half_height);
+                Some((origin, forward))
+            }
+            Projection::Equirectangular => {
+                // x recorre la longitud completa y y la latitud, con el centro de la imagen mirando a `center`
+                let longitude = screen_x * std::f32::consts::PI;
+                let latitude = screen_y * std::f32::consts::FRAC_PI_2;
+                let local = Vec3::new(
+                    longitude.sin() * latitude.cos(),
+                    latitude.sin(),
+                    -longitude.cos() * latitude.cos(),
+                );
+                Some((self.eye, self.basis_change(&local)))
+            }
+            Projection::Fisheye => {
+                // Equidistante: el ángulo respecto al eje de vista crece linealmente con el radio
+                let x = screen_x * aspect_ratio;
+                let radius = (x * x + screen_y * screen_y).sqrt();
+                if radius > 1.0 {
+                    return None;
+                }
+                let theta = radius * self.fisheye_fov.to_radians() * 0.5;
+                let phi = screen_y.atan2(x);
+                let local = Vec3::new(theta.sin() * phi.cos(), theta.sin() * phi.sin(), -theta.cos());
+                Some((self.eye, self.basis_change(&local)))
+            }
+        }
+    }
+
+    // Pasar a la siguiente proyección
+    pub fn cycle_projection(&mut self) {
+        self.projection = match self.projection {
+            Projection::Perspective => Projection::Orthographic,
+            Projection::Orthographic => Projection::Equirectangular,
+            Projection::Equirectangular => Projection::Fisheye,
+            Projection::Fisheye => Projection::Perspective,
+        };
+    }
+
+    // Las proyecciones panorámicas ven fuera del frustum, así que no se puede recortar por él
+    pub fn is_panoramic(&self) -> bool {
+        matches!(self.projection, Projection::Equirectangular | Projection::Fisheye)
+    }
+
+    // Colocar la cámara en un ángulo predefinido alrededor de `center`, con proyección ortográfica
+    pub fn apply_preset(&mut self, preset: ViewPreset) {
+        let distance = (self.eye - self.center).magnitude();
+        let yaw = std::f32::consts::FRAC_PI_4;
+        let elevation = preset.elevation();
+
+        self.eye = self.center + Vec3::new(
+            distance * elevation.cos() * yaw.cos(),
+            distance * elevation.sin(),
+            distance * elevation.cos() * yaw.sin(),
+        );
+        self.up = Vec3::new(0.0, 1.0, 0.0);
+        self.projection = Projection::Orthographic;
+    }
+
+    // Rango de distancias sobre un rayo que queda entre los planos de recorte
+    pub fn clip_range(&self, ray_direction: &Vec3) -> (f32, f32) {
+        if self.is_panoramic() {
+            // Sin plano de imagen, los planos de recorte pasan a ser esferas alrededor del ojo
+            return (self.near_plane, self.far_plane);
+        }
+        let cos_angle = ray_direction.dot(&self.view_direction()).max(1e-6);
+        (self.near_plane / cos_angle, self.far_plane / cos_angle)
+    }
+
+    // Cambiar el campo de visión (zoom) dentro de un rango razonable.
+    // En ortográfica se escala el alto visible en la misma proporción.
+    pub fn zoom(&mut self, delta_fov: f32) {
+        match self.projection {
+            Projection::Perspective => self.fov = (self.fov + delta_fov).clamp(10.0, 120.0),
+            Projection::Orthographic => {
+                self.ortho_height = (self.ortho_height * (1.0 + delta_fov / self.fov)).clamp(0.5, 50.0)
+            }
+            Projection::Fisheye => self.fisheye_fov = (self.fisheye_fov + delta_fov).clamp(90.0, 360.0),
+            Projection::Equirectangular => {} // El panorama siempre cubre la esfera completa
+        }
+    }
+
     // Cambio de base
     pub fn basis_change(&self, vector: &Vec3) -> Vec3 {
         let forward = (self.center - self.eye).normalize(); // Eje z negativo
@@ -56,6 +232,58 @@ impl Camera {
         rotated.normalize()
     }
 
+    // Rayo de lente delgada: el origen se desplaza sobre la apertura y apunta al punto en foco.
+    // (lens_u, lens_v) es una muestra dentro del disco unitario.
+    pub fn lens_ray(&self, origin: &Vec3, direction: &Vec3, lens_u: f32, lens_v: f32) -> (Vec3, Vec3) {
+        if self.aperture <= 0.0 {
+            return (*origin, *direction);
+        }
+
+        let forward = self.view_direction();
+        let right = forward.cross(&self.up).normalize();
+        let up = right.cross(&forward).normalize();
+
+        // Todos los rayos del pixel convergen en el mismo punto del plano de enfoque
+        let focus_point = origin + direction * (self.focus_distance / direction.dot(&forward));
+        let lens_origin = origin + (right * lens_u + up * lens_v) * self.aperture;
+
+        (lens_origin, (focus_point - lens_origin).normalize())
+    }
+
+    // Enfocar el plano de enfoque sobre un punto del mundo
+    pub fn focus_on(&mut self, point: &Vec3) {
+        let distance = (point - self.eye).dot(&self.view_direction());
+        if distance > 0.0 {
+            self.focus_distance = distance;
+        }
+    }
+
+    // Pasar al siguiente modo estereoscópico
+    pub fn cycle_stereo(&mut self) {
+        self.stereo = match self.stereo {
+            StereoMode::Mono => StereoMode::Anaglyph,
+            StereoMode::Anaglyph => StereoMode::SideBySide,
+            StereoMode::SideBySide => StereoMode::OverUnder,
+            StereoMode::OverUnder => StereoMode::Mono,
+        };
+    }
+
+    // Cámara de un solo ojo: se desplaza media distancia interocular y gira hacia el punto de convergencia
+    pub fn eye_camera(&self, eye: Eye) -> Camera {
+        let forward = self.view_direction();
+        let right = forward.cross(&self.up).normalize();
+        let offset = match eye {
+            Eye::Left => -self.interocular_distance / 2.0,
+            Eye::Right => self.interocular_distance / 2.0,
+        };
+
+        let mut camera = self.clone();
+        camera.eye = self.eye + right * offset;
+        camera.center = self.eye + forward * self.convergence_distance;
+        camera.stereo = StereoMode::Mono;
+        camera
+    }
+
     // Método para mover la cámara alrededor del centro en órbita
     pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
         // Calcular el vector desde el centro al ojo (vector de radio) y medir la distancia
@@ -83,6 +311,33 @@ impl Camera {
         self.eye = new_eye;
     }
 
+    // Alternar entre órbita y vuelo libre
+    pub fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            CameraMode::Orbit => CameraMode::Fly,
+            CameraMode::Fly => CameraMode::Orbit,
+        };
+    }
+
+    // Girar la mirada desde el ojo (yaw/pitch en radianes), moviendo `center` y no `eye`
+    pub fn look(&mut self, delta_yaw: f32, delta_pitch: f32) {
+        let direction = self.view_direction();
+        let distance = (self.center - self.eye).magnitude();
+
+        let yaw = direction.z.atan2(direction.x) + delta_yaw;
+        let pitch = (direction.y.asin() + delta_pitch).clamp(-std::f32::consts::PI / 2.0 + 0.1, std::f32::consts::PI / 2.0 - 0.1);
+
+        let new_direction = Vec3::new(yaw.cos() * pitch.cos(), pitch.sin(), yaw.sin() * pitch.cos());
+        self.center = self.eye + new_direction * distance;
+    }
+
+    // Desplazamiento relativo a la vista: adelante, derecha y arriba (en unidades del mundo)
+    pub fn fly(&mut self, forward: f32, right: f32, up: f32) {
+        let forward_dir = self.view_direction();
+        let right_dir = forward_dir.cross(&self.up).normalize();
+        self.move_camera(forward_dir * forward + right_dir * right + self.up * up);
+    }
+
     // Método para actualizar la posición de la cámara
     pub fn move_camera(&mut self, delta: Vec3) {
         self.eye += delta;
@@ -0,0 +1,190 @@
+use nalgebra_glm::Vec3;
+use crate::camera::Camera;
+use std::fs;
+use std::path::Path;
+
+// Un punto de control del recorrido de cámara
+#[derive(Debug, Clone, Copy)]
+pub struct CameraKeyframe {
+    pub time: f32,    // Segundos desde el inicio del recorrido
+    pub eye: Vec3,
+    pub center: Vec3,
+    pub fov: f32,     // En grados
+}
+
+// Curva usada entre keyframes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    CatmullRom, // Pasa por cada keyframe con tangentes de los vecinos, según el tiempo entre ellos
+    Bezier,     // Bézier cúbica con manijas de largo proporcional al tramo (velocidad más pareja)
+}
+
+#[derive(Debug, Clone)]
+pub struct CameraPath {
+    pub keyframes: Vec<CameraKeyframe>,
+    pub interpolation: Interpolation,
+}
+
+impl CameraPath {
+    pub fn new(interpolation: Interpolation) -> Self {
+        CameraPath {
+            keyframes: Vec::new(),
+            interpolation,
+        }
+    }
+
+    // Agregar la pose actual de la cámara como keyframe en `time`
+    pub fn record(&mut self, camera: &Camera, time: f32) {
+        self.keyframes.push(CameraKeyframe {
+            time,
+            eye: camera.eye,
+            center: camera.center,
+            fov: camera.fov,
+        });
+        self.keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+
+    // Duración total del recorrido
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |k| k.time)
+    }
+
+    // Cantidad de cuadros para reproducir el recorrido completo a `fps`
+    pub fn frame_count(&self, fps: f32) -> usize {
+        (self.duration() * fps).ceil() as usize + 1
+    }
+
+    // Pose interpolada en `time` (se limita al rango del recorrido)
+    pub fn sample(&self, time: f32) -> Option<CameraKeyframe> {
+        let keys = &self.keyframes;
+        let first = keys.first()?;
+        let last = keys.last()?;
+        if keys.len() == 1 || time <= first.time {
+            return Some(*first);
+        }
+        if time >= last.time {
+            return Some(*last);
+        }
+
+        // Tramo [i, i + 1] que contiene `time`
+        let i = keys.iter().rposition(|k| k.time <= time).unwrap_or(0).min(keys.len() - 2);
+        let k0 = keys[i.saturating_sub(1)];
+        let k1 = keys[i];
+        let k2 = keys[i + 1];
+        let k3 = keys[(i + 2).min(keys.len() - 1)];
+        let span = (k2.time - k1.time).max(1e-6);
+        let u = (time - k1.time) / span;
+        // Tramos vecinos; en los extremos se repite el keyframe y se toma el tramo actual
+        let before = if i == 0 { span } else { (k1.time - k0.time).max(1e-6) };
+        let after = if i + 2 >= keys.len() { span } else { (k3.time - k2.time).max(1e-6) };
+        let weights = (span / (before + span), span / (span + after));
+
+        let curve = |p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3| match self.interpolation {
+            Interpolation::CatmullRom => catmull_rom(p0, p1, p2, p3, weights, u),
+            Interpolation::Bezier => smooth_bezier(p0, p1, p2, p3, u),
+        };
+        let fov = curve(
+            Vec3::new(k0.fov, 0.0, 0.0),
+            Vec3::new(k1.fov, 0.0, 0.0),
+            Vec3::new(k2.fov, 0.0, 0.0),
+            Vec3::new(k3.fov, 0.0, 0.0),
+        ).x;
+
+        Some(CameraKeyframe {
+            time,
+            eye: curve(k0.eye, k1.eye, k2.eye, k3.eye),
+            center: curve(k0.center, k1.center, k2.center, k3.center),
+            fov,
+        })
+    }
+
+    // Colocar la cámara en la pose del recorrido en `time`
+    pub fn apply(&self, time: f32, camera: &mut Camera) {
+        if let Some(key) = self.sample(time) {
+            camera.eye = key.eye;
+            camera.center = key.center;
+            camera.fov = key.fov;
+        }
+    }
+
+    // Formato por líneas, igual que las escenas:
+    //
+    //   interpolation catmull_rom
+    //   key <tiempo> <eye x y z> <center x y z> <fov>
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let mut text = String::from("# Recorrido de cámara: key <tiempo> <eye x y z> <center x y z> <fov>\n");
+        let interpolation = match self.interpolation {
+            Interpolation::CatmullRom => "catmull_rom",
+            Interpolation::Bezier => "bezier",
+        };
+        text.push_str(&format!("interpolation {}\n", interpolation));
+        for k in &self.keyframes {
+            text.push_str(&format!(
+                "key {} {} {} {} {} {} {} {}\n",
+                k.time, k.eye.x, k.eye.y, k.eye.z, k.center.x, k.center.y, k.center.z, k.fov
+            ));
+        }
+        fs::write(path.as_ref(), text)
+            .map_err(|e| format!("No se pudo guardar {}: {}", path.as_ref().display(), e))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let text = fs::read_to_string(path.as_ref())
+            .map_err(|e| format!("No se pudo leer {}: {}", path.as_ref().display(), e))?;
+
+        let mut camera_path = CameraPath::new(Interpolation::CatmullRom);
+        for (index, raw_line) in text.lines().enumerate() {
+            let line = raw_line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let error = |msg: &str| format!("Línea {}: {}", index + 1, msg);
+
+            match tokens.as_slice() {
+                ["interpolation", "catmull_rom"] => camera_path.interpolation = Interpolation::CatmullRom,
+                ["interpolation", "bezier"] => camera_path.interpolation = Interpolation::Bezier,
+                ["key", values @ ..] if values.len() == 8 => {
+                    let numbers: Result<Vec<f32>, _> = values.iter().map(|v| v.parse::<f32>()).collect();
+                    let n = numbers.map_err(|_| error("número inválido"))?;
+                    camera_path.keyframes.push(CameraKeyframe {
+                        time: n[0],
+                        eye: Vec3::new(n[1], n[2], n[3]),
+                        center: Vec3::new(n[4], n[5], n[6]),
+                        fov: n[7],
+                    });
+                }
+                _ => return Err(error(&format!("línea no reconocida '{}'", line))),
+            }
+        }
+
+        camera_path.keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Ok(camera_path)
+    }
+}
+
+// Spline de Catmull-Rom entre p1 y p2 en forma de Hermite. Las tangentes son las diferencias
+// p2 - p0 y p3 - p1 escaladas por `weights`, la fracción que ocupa el tramo actual del intervalo
+// de tiempo entre esos vecinos; con keyframes equiespaciados ambos pesos son 0.5 (la spline uniforme).
+fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, weights: (f32, f32), u: f32) -> Vec3 {
+    let m1 = (p2 - p0) * weights.0;
+    let m2 = (p3 - p1) * weights.1;
+    let u2 = u * u;
+    let u3 = u2 * u;
+    p1 * (2.0 * u3 - 3.0 * u2 + 1.0) + m1 * (u3 - 2.0 * u2 + u) + p2 * (3.0 * u2 - 2.0 * u3) + m2 * (u3 - u2)
+}
+
+// Bézier cúbica entre p1 y p2; las manijas siguen la dirección de los vecinos
+// con un largo de un tercio del tramo
+fn smooth_bezier(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, u: f32) -> Vec3 {
+    let segment = (p2 - p1).magnitude() / 3.0;
+    let handle = |a: Vec3, b: Vec3| -> Vec3 {
+        let d = b - a;
+        if d.magnitude() > 1e-6 { d.normalize() * segment } else { Vec3::zeros() }
+    };
+    let c1 = p1 + handle(p0, p2);
+    let c2 = p2 - handle(p1, p3);
+
+    let v = 1.0 - u;
+    p1 * (v * v * v) + c1 * (3.0 * v * v * u) + c2 * (3.0 * v * u * u) + p2 * (u * u * u)
+}
@@ -1,22 +1,26 @@
-use nalgebra_glm::{Vec3, dot};
+use nalgebra_glm::Vec3;
 use crate::color::Color;
 use crate::ray_intersect::{Intersect, RayIntersect};
 use crate::light::Light;
+use crate::stats;
 
 fn reflect(incident: &Vec3, normal: &Vec3) -> Vec3 {
     incident - 2.0 * incident.dot(normal) * normal
 }
 
-// Función para calcular sombras
-fn cast_shadow(intersect: &Intersect, light: &Light, objects: &[Box<dyn RayIntersect>]) -> f32 {
+// Función para calcular sombras: 1.0 si algún objeto tapa la luz antes de llegar a ella
+pub fn cast_shadow(intersect: &Intersect, light: &Light, objects: &[Box<dyn RayIntersect>]) -> f32 {
     let light_dir = (light.position - intersect.point).normalize();
+    let light_distance = (light.position - intersect.point).magnitude();
     let shadow_ray_origin = intersect.point + intersect.normal * 1e-3;
+    stats::count_shadow_ray();
 
     let mut shadow_intensity = 0.0;
 
     for object in objects {
+        stats::count_intersection_tests(1);
         let shadow_intersect = object.ray_intersect(&shadow_ray_origin, &light_dir);
-        if shadow_intersect.is_intersecting && shadow_intersect.distance > 1e-3 {
+        if shadow_intersect.is_intersecting && shadow_intersect.distance > 1e-3 && shadow_intersect.distance < light_distance {
             shadow_intensity = 1.0;
             break;
         }
@@ -26,7 +30,7 @@ fn cast_shadow(intersect: &Intersect, light: &Light, objects: &[Box<dyn RayInter
 }
 
 fn refract(incident: &Vec3, normal: &Vec3, eta_t: f32) -> Vec3 {
-    let cosi = -incident.dot(normal).max(-1.0).min(1.0);
+    let cosi = -incident.dot(normal).clamp(-1.0, 1.0);
     let (n_cosi, eta, n_normal);
 
     if cosi < 0.0 {
@@ -48,30 +52,54 @@ fn refract(incident: &Vec3, normal: &Vec3, eta_t: f32) -> Vec3 {
     }
 }
 
-pub fn cast_ray(ray_origin: &Vec3, ray_direction: &Vec3, objects: &[Box<dyn RayIntersect>], light: &Light, depth: u32) -> Color {
-    if depth > 3 {
-        return Color::new(135, 206, 235); // Color del cielo
-    }
+// Busca la intersección más cercana entre todos los objetos de la escena
+pub fn nearest_intersect(ray_origin: &Vec3, ray_direction: &Vec3, objects: &[Box<dyn RayIntersect>]) -> Intersect {
+    nearest_intersect_in_range(ray_origin, ray_direction, objects, (f32::NEG_INFINITY, f32::INFINITY))
+}
 
+// Igual que nearest_intersect, pero ignora impactos fuera de (t_min, t_max)
+pub fn nearest_intersect_in_range(ray_origin: &Vec3, ray_direction: &Vec3, objects: &[Box<dyn RayIntersect>], range: (f32, f32)) -> Intersect {
+    nearest_hit_in_range(ray_origin, ray_direction, objects, range).1
+}
+
+// Intersección más cercana dentro de (t_min, t_max) junto con el índice del objeto impactado
+pub fn nearest_hit_in_range(ray_origin: &Vec3, ray_direction: &Vec3, objects: &[Box<dyn RayIntersect>], (t_min, t_max): (f32, f32)) -> (Option<usize>, Intersect) {
     let mut intersect = Intersect::empty();
-    let mut zbuffer = f32::INFINITY;
+    let mut object_index = None;
+    let mut zbuffer = t_max;
+    stats::count_intersection_tests(objects.len());
 
     // Iterar sobre cualquier objeto que implemente RayIntersect
-    for object in objects {
-        let tmp = object.ray_intersect(ray_origin, ray_direction);
-        if tmp.is_intersecting && tmp.distance < zbuffer {
+    for (index, object) in objects.iter().enumerate() {
+        let tmp = object.ray_intersect_from(ray_origin, ray_direction, t_min);
+        if tmp.is_intersecting && tmp.distance >= t_min && tmp.distance < zbuffer {
             zbuffer = tmp.distance;
             intersect = tmp;
+            object_index = Some(index);
         }
     }
 
+    (object_index, intersect)
+}
+
+pub fn cast_ray(ray_origin: &Vec3, ray_direction: &Vec3, objects: &[Box<dyn RayIntersect>], light: &Light, depth: u32) -> Color {
+    if depth > 3 {
+        return light.sky.color(ray_direction); // Color del cielo
+    }
+
+    stats::count_secondary_ray();
+    let intersect = nearest_intersect(ray_origin, ray_direction, objects);
+    shade(&intersect, ray_origin, ray_direction, objects, light, depth)
+}
+
+// Calcula el color de un impacto ya encontrado (o el fondo si no hubo impacto)
+pub fn shade(intersect: &Intersect, ray_origin: &Vec3, ray_direction: &Vec3, objects: &[Box<dyn RayIntersect>], light: &Light, depth: u32) -> Color {
     if !intersect.is_intersecting {
-        return Color::new(135, 206, 235); // Color de fondo
+        return light.sky.color(ray_direction); // Color de fondo
     }
 
-    // Luz ambiental
-    let ambient_intensity = 0.3; // Aumenta este valor para intensificar la luz ambiental
-    let ambient_color = Color::new(255, 255, 255) * ambient_intensity; // Luz ambiental blanca
+    // Luz ambiental (la del cielo: blanca de día, azul oscura de noche)
+    let ambient_color = light.sky.ambient;
 
     // Reflexión y refracción
     let mut reflect_color = Color::black();
@@ -95,13 +123,16 @@ pub fn cast_ray(ray_origin: &Vec3, ray_direction: &Vec3, objects: &[Box<dyn RayI
     let view_dir = (ray_origin - intersect.point).normalize();
     let reflect_dir = reflect(&(-light_dir), &intersect.normal);
 
-    let diffuse_intensity = intersect.normal.dot(&light_dir).max(0.0).min(1.0);
+    let diffuse_intensity = intersect.normal.dot(&light_dir).clamp(0.0, 1.0);
     let diffuse_color = intersect.material.get_diffuse_color(intersect.u, intersect.v);
     let diffuse = diffuse_color * intersect.material.albedo[0] * diffuse_intensity * light.intensity;
     
     let specular_intensity = view_dir.dot(&reflect_dir).max(0.0).powf(intersect.material.specular);
     let specular = light.color * intersect.material.albedo[1] * specular_intensity * light.intensity;
 
+    // Los materiales emisivos brillan con su propio color, sin depender de la luz
+    let emission = diffuse_color * intersect.material.emission;
+
     // Calcular el color final
-    (ambient_color + diffuse + specular) * (1.0 - reflectivity - transparency) + (reflect_color * reflectivity) + (refract_color * transparency)
+    (ambient_color + diffuse + specular + emission) * (1.0 - reflectivity - transparency) + (reflect_color * reflectivity) + (refract_color * transparency)
 }
@@ -0,0 +1,73 @@
+// Reloj de la simulación: avanza en pasos fijos, separado de la frecuencia de render.
+// Cada cuadro se le pasa el tiempo real transcurrido y devuelve cuántos pasos simular;
+// lo que sobra se guarda para el cuadro siguiente.
+pub struct SimulationClock {
+    pub step: f32,       // Duración de un paso de simulación, en segundos
+    pub time_scale: f32, // 1 = tiempo real, 0.5 = cámara lenta, 2 = doble de rápido
+    pub paused: bool,
+    pub time: f32,       // Tiempo simulado, siempre un múltiplo de `step`
+    pub steps: u64,      // Pasos simulados desde el inicio
+    accumulator: f32,    // Tiempo escalado que todavía no alcanza para un paso
+    pending_steps: u32,  // Pasos pedidos uno a uno con la simulación en pausa
+}
+
+// Pasos por segundo del reloj de la ventana; las salidas sin ventana simulan al mismo ritmo
+pub const STEPS_PER_SECOND: f32 = 60.0;
+
+// Tope de pasos por cuadro: tras un cuadro muy lento se descarta el atraso en lugar de
+// simular tanto que el cuadro siguiente también sea lento
+const MAX_STEPS_PER_FRAME: u32 = 8;
+const MIN_TIME_SCALE: f32 = 1.0 / 16.0;
+const MAX_TIME_SCALE: f32 = 8.0;
+
+impl SimulationClock {
+    pub fn new(steps_per_second: f32) -> Self {
+        SimulationClock {
+            step: 1.0 / steps_per_second,
+            time_scale: 1.0,
+            paused: false,
+            time: 0.0,
+            steps: 0,
+            accumulator: 0.0,
+            pending_steps: 0,
+        }
+    }
+
+    // Sumar el tiempo real del cuadro y devolver cuántos pasos fijos hay que simular
+    pub fn advance(&mut self, real_delta: f32) -> u32 {
+        let steps = if self.paused {
+            self.accumulator = 0.0;
+            std::mem::take(&mut self.pending_steps)
+        } else {
+            self.accumulator += real_delta * self.time_scale;
+            let steps = (self.accumulator / self.step) as u32;
+            self.accumulator -= steps as f32 * self.step;
+            if steps > MAX_STEPS_PER_FRAME {
+                self.accumulator = 0.0;
+            }
+            steps.min(MAX_STEPS_PER_FRAME)
+        };
+
+        self.steps += steps as u64;
+        self.time = self.steps as f32 * self.step;
+        steps
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.pending_steps = 0;
+    }
+
+    // Avanzar un único paso: pausa la simulación si estaba corriendo
+    pub fn single_step(&mut self) {
+        if !self.paused {
+            self.toggle_pause();
+        }
+        self.pending_steps += 1;
+    }
+
+    // Multiplicar la escala de tiempo, dentro de [1/16, 8]
+    pub fn scale_by(&mut self, factor: f32) {
+        self.time_scale = (self.time_scale * factor).clamp(MIN_TIME_SCALE, MAX_TIME_SCALE);
+    }
+}
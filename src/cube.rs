@@ -8,6 +8,8 @@ pub struct Cube {
     pub center: Vec3,
     pub size: f32,
     pub material: Arc<Material>,
+    pub rotation: f32, // Giro alrededor del eje vertical que pasa por el centro, en radianes
+    pub height: f32,   // Fracción del alto que ocupa, desde la base (1 = cubo completo)
 }
 
 impl Cube {
@@ -16,6 +18,8 @@ impl Cube {
             center,
             size,
             material,
+            rotation: 0.0,
+            height: 1.0,
         }
     }
 
@@ -24,9 +28,9 @@ impl Cube {
         self.center - Vec3::new(self.size / 2.0, self.size / 2.0, self.size / 2.0)
     }
 
-    // Calcula el punto máximo del cubo (esquina superior derecha)
+    // Calcula el punto máximo del cubo (esquina superior derecha); un cubo bajo se recorta por arriba
     pub fn max(&self) -> Vec3 {
-        self.center + Vec3::new(self.size / 2.0, self.size / 2.0, self.size / 2.0)
+        self.center + Vec3::new(self.size / 2.0, self.size * (self.height - 0.5), self.size / 2.0)
     }
 
     // Obtener la posición del cubo (equivalente a su centro)
@@ -34,11 +38,6 @@ impl Cube {
         self.center
     }
 
-    // Establecer una nueva posición (actualizar el centro)
-    pub fn set_position(&mut self, new_position: Vec3) {
-        self.center = new_position;
-    }
-
     // Obtener coordenadas UV
     pub fn get_uv(&self, point: &Vec3, normal: &Vec3) -> (f32, f32) {
         let mut u = 0.0;
@@ -84,6 +83,43 @@ impl Cube {
 
 impl RayIntersect for Cube {
     fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        self.ray_intersect_from(ray_origin, ray_direction, f32::NEG_INFINITY)
+    }
+
+    // Implementación del método `position` que devuelve el centro del cubo
+    fn position(&self) -> Vec3 {
+        self.position()
+    }
+
+    fn ray_intersect_from(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_min: f32) -> Intersect {
+        if self.rotation == 0.0 {
+            return self.intersect_aligned(ray_origin, ray_direction, t_min);
+        }
+
+        // Cubo girado: el rayo se lleva al espacio del cubo sin girar y el impacto se gira de vuelta.
+        // El giro es rígido, así que la distancia no cambia.
+        let local_origin = rotate_y(&(ray_origin - self.center), -self.rotation) + self.center;
+        let local_direction = rotate_y(ray_direction, -self.rotation);
+        let mut intersect = self.intersect_aligned(&local_origin, &local_direction, t_min);
+        if intersect.is_intersecting {
+            intersect.point = rotate_y(&(intersect.point - self.center), self.rotation) + self.center;
+            intersect.normal = rotate_y(&intersect.normal, self.rotation);
+        }
+        intersect
+    }
+}
+
+// Girar un vector `angle` radianes alrededor del eje y
+fn rotate_y(v: &Vec3, angle: f32) -> Vec3 {
+    let (sin, cos) = angle.sin_cos();
+    Vec3::new(v.x * cos + v.z * sin, v.y, -v.x * sin + v.z * cos)
+}
+
+impl Cube {
+    // Intersección con el cubo alineado a los ejes (sin giro). Si la entrada queda antes de
+    // `min_distance` y la salida no, el impacto es la salida, con la normal hacia adentro (la cara
+    // se ve por dentro).
+    fn intersect_aligned(&self, ray_origin: &Vec3, ray_direction: &Vec3, min_distance: f32) -> Intersect {
         let t_min = (self.min() - ray_origin).component_div(ray_direction);
         let t_max = (self.max() - ray_origin).component_div(ray_direction);
 
@@ -93,32 +129,36 @@ impl RayIntersect for Cube {
         let t_near = t1.max();
         let t_far = t2.min();
 
-        if t_near < t_far && t_far > 0.0 {
-            let distance = t_near;
+        if t_near < t_far && t_far > 0.0 && t_far >= min_distance {
+            let exiting = t_near < min_distance;
+            let distance = if exiting { t_far } else { t_near };
             let point = ray_origin + ray_direction * distance;
 
-            // Determinar la normal utilizando el centro
+            // Determinar la normal según la cara del cubo en la que cae el punto
             let mut normal = Vec3::new(0.0, 0.0, 0.0);
 
             let epsilon = 0.001;
-            let center_to_point = point - self.center;
-
-            if (center_to_point.x.abs() - (self.max().x - self.center.x)).abs() < epsilon {
-                normal.x = center_to_point.x.signum();
-            } else if (center_to_point.x.abs() - (self.center.x - self.min().x)).abs() < epsilon {
-                normal.x = -center_to_point.x.signum();
-            } else if (center_to_point.y.abs() - (self.max().y - self.center.y)).abs() < epsilon {
-                normal.y = center_to_point.y.signum();
-            } else if (center_to_point.y.abs() - (self.center.y - self.min().y)).abs() < epsilon {
-                normal.y = -center_to_point.y.signum();
-            } else if (center_to_point.z.abs() - (self.max().z - self.center.z)).abs() < epsilon {
-                normal.z = center_to_point.z.signum();
-            } else if (center_to_point.z.abs() - (self.center.z - self.min().z)).abs() < epsilon {
-                normal.z = -center_to_point.z.signum();
+            let (min, max) = (self.min(), self.max());
+
+            if (point.x - max.x).abs() < epsilon {
+                normal.x = 1.0;
+            } else if (point.x - min.x).abs() < epsilon {
+                normal.x = -1.0;
+            } else if (point.y - max.y).abs() < epsilon {
+                normal.y = 1.0;
+            } else if (point.y - min.y).abs() < epsilon {
+                normal.y = -1.0;
+            } else if (point.z - max.z).abs() < epsilon {
+                normal.z = 1.0;
+            } else if (point.z - min.z).abs() < epsilon {
+                normal.z = -1.0;
             }
 
             // Obtener coordenadas UV
             let (u, v) = self.get_uv(&point, &normal);
+            if exiting {
+                normal = -normal;
+            }
 
             return Intersect {
                 distance,
@@ -141,9 +181,4 @@ impl RayIntersect for Cube {
             v: 0.0,
         }
     }
-
-    // Implementación del método `position` que devuelve el centro del cubo
-    fn position(&self) -> Vec3 {
-        self.position()
-    }
 }
@@ -0,0 +1,84 @@
+use nalgebra_glm::Vec3;
+use crate::color::Color;
+use crate::light::Light;
+use crate::sky::{mix, Sky};
+use std::f32::consts::TAU;
+
+// Distancia a la que se coloca la luz del sol o la luna: tan lejos que llega casi paralela
+const CELESTIAL_DISTANCE: f32 = 1000.0;
+// Inclinación del arco hacia la cámara por defecto (+z), para que de día se ilumine el frente
+const ARC_TILT: f32 = 0.5;
+
+const SUN_INTENSITY: f32 = 3.0;
+const MOON_INTENSITY: f32 = 0.6;
+
+// Hora del día que mueve al sol y a la luna en arcos opuestos y ajusta luz y cielo.
+// El sol sale por +x a las 6, pasa por arriba a las 12 y se pone por -x a las 18.
+#[derive(Clone)]
+pub struct DayCycle {
+    pub enabled: bool,
+    pub hour: f32,       // Hora actual en [0, 24)
+    pub day_length: f32, // Segundos de simulación que dura un día completo
+}
+
+impl DayCycle {
+    pub fn new(hour: f32, day_length: f32) -> Self {
+        DayCycle {
+            enabled: false,
+            hour: hour.rem_euclid(24.0),
+            day_length,
+        }
+    }
+
+    // Avanzar `seconds` segundos de simulación
+    pub fn advance(&mut self, seconds: f32) {
+        if self.day_length > 0.0 {
+            self.shift_hours(seconds / self.day_length * 24.0);
+        }
+    }
+
+    pub fn shift_hours(&mut self, hours: f32) {
+        self.hour = (self.hour + hours).rem_euclid(24.0);
+    }
+
+    // Dirección hacia el sol; la luna está en la opuesta
+    pub fn sun_direction(&self) -> Vec3 {
+        let angle = (self.hour - 6.0) / 24.0 * TAU;
+        Vec3::new(angle.cos(), angle.sin(), ARC_TILT).normalize()
+    }
+
+    // Colocar la luz en el sol (o en la luna de noche) alrededor de `center` y ajustar el cielo
+    pub fn apply(&self, light: &mut Light, center: Vec3) {
+        let sun = self.sun_direction();
+        let elevation = sun.y;
+        // 0 de noche, 1 de día, con una transición alrededor del horizonte
+        let daylight = smoothstep(-0.15, 0.25, elevation);
+        // Máximo con el sol en el horizonte: amanecer y atardecer
+        let twilight = 1.0 - (elevation.abs() / 0.3).min(1.0);
+
+        if elevation >= 0.0 {
+            light.position = center + sun * CELESTIAL_DISTANCE;
+            light.color = mix(Color::new(255, 140, 70), Color::new(255, 244, 229), smoothstep(0.0, 0.4, elevation));
+            light.intensity = SUN_INTENSITY * smoothstep(0.0, 0.15, elevation);
+        } else {
+            light.position = center - sun * CELESTIAL_DISTANCE;
+            light.color = Color::new(140, 160, 220);
+            light.intensity = MOON_INTENSITY * smoothstep(0.0, 0.15, -elevation);
+        }
+
+        let day_zenith = mix(Color::new(70, 130, 215), Color::new(70, 80, 150), twilight);
+        let day_horizon = mix(Color::new(170, 215, 240), Color::new(250, 140, 80), twilight);
+        light.sky = Sky {
+            zenith: mix(Color::new(4, 6, 18), day_zenith, daylight),
+            horizon: mix(Color::new(15, 20, 40), day_horizon, daylight),
+            ambient: mix(Color::new(18, 22, 40), Color::new(76, 76, 76), daylight),
+            stars: 1.0 - daylight,
+            sun_direction: Some(sun),
+        };
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
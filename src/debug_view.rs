@@ -0,0 +1,120 @@
+use nalgebra_glm::Vec3;
+use crate::cast_ray::cast_shadow;
+use crate::color::Color;
+use crate::light::Light;
+use crate::material::NO_MATERIAL_ID;
+use crate::output::id_color;
+use crate::ray_intersect::{Intersect, RayIntersect};
+
+// Qué se dibuja en cada pixel: la imagen sombreada o una visualización de depuración
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DebugView {
+    Shaded,              // Sombreado normal de cast_ray
+    Normals,             // Normal del impacto remapeada a RGB
+    UvChecker,           // Tablero sobre las coordenadas UV, teñido por (u, v)
+    Distance,            // Distancia al impacto: cerca = claro
+    MaterialId,          // Un color distinto por material
+    ShadowOcclusion,     // Blanco si la luz llega al punto, oscuro si un objeto la tapa
+    IntersectionHeatmap, // Pruebas rayo-objeto por pixel, de azul (pocas) a rojo (muchas)
+}
+
+// Casillas del tablero por unidad de UV
+const CHECKER_SIZE: f32 = 8.0;
+// Distancia a la que el gris de la vista de distancia cae a la mitad
+const DISTANCE_HALF: f32 = 5.0;
+
+impl DebugView {
+    pub fn cycle(&self) -> DebugView {
+        match self {
+            DebugView::Shaded => DebugView::Normals,
+            DebugView::Normals => DebugView::UvChecker,
+            DebugView::UvChecker => DebugView::Distance,
+            DebugView::Distance => DebugView::MaterialId,
+            DebugView::MaterialId => DebugView::ShadowOcclusion,
+            DebugView::ShadowOcclusion => DebugView::IntersectionHeatmap,
+            DebugView::IntersectionHeatmap => DebugView::Shaded,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DebugView::Shaded => "shaded",
+            DebugView::Normals => "normals",
+            DebugView::UvChecker => "uv",
+            DebugView::Distance => "distance",
+            DebugView::MaterialId => "material",
+            DebugView::ShadowOcclusion => "shadow",
+            DebugView::IntersectionHeatmap => "heatmap",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<DebugView> {
+        let mut view = DebugView::Shaded;
+        loop {
+            if view.name() == name {
+                return Some(view);
+            }
+            view = view.cycle();
+            if view == DebugView::Shaded {
+                return None;
+            }
+        }
+    }
+}
+
+// Color de depuración del impacto primario (negro donde no hay impacto).
+// El mapa de calor se colorea aparte, cuando se conoce el máximo del cuadro.
+pub fn debug_color(view: DebugView, intersect: &Intersect, objects: &[Box<dyn RayIntersect>], light: &Light) -> Color {
+    if !intersect.is_intersecting {
+        return Color::black();
+    }
+
+    match view {
+        DebugView::Normals => to_color(intersect.normal * 0.5 + Vec3::repeat(0.5)),
+        DebugView::UvChecker => {
+            let checker = ((intersect.u * CHECKER_SIZE).floor() + (intersect.v * CHECKER_SIZE).floor()) as i32 % 2 == 0;
+            let tint = Vec3::new(intersect.u, intersect.v, 0.5);
+            to_color(if checker { tint } else { tint * 0.35 })
+        }
+        DebugView::Distance => {
+            let brightness = 1.0 - intersect.distance / (intersect.distance + DISTANCE_HALF);
+            to_color(Vec3::repeat(brightness))
+        }
+        DebugView::MaterialId => {
+            // Los materiales fuera de la lista (partículas) van en gris
+            let [r, g, b] = match intersect.material.id {
+                NO_MATERIAL_ID => [128; 3],
+                id => id_color(id as f32),
+            };
+            Color::new(r, g, b)
+        }
+        DebugView::ShadowOcclusion => {
+            let facing = intersect.normal.dot(&(light.position - intersect.point).normalize()) > 0.0;
+            if facing && cast_shadow(intersect, light, objects) == 0.0 {
+                Color::new(255, 255, 255)
+            } else {
+                Color::new(40, 40, 60)
+            }
+        }
+        DebugView::Shaded | DebugView::IntersectionHeatmap => Color::black(),
+    }
+}
+
+// Paleta azul -> verde -> amarillo -> rojo para un valor en [0, 1]
+pub fn heat_color(value: f32) -> Color {
+    let stops = [
+        Vec3::new(0.0, 0.0, 0.5),
+        Vec3::new(0.0, 0.4, 1.0),
+        Vec3::new(0.0, 0.9, 0.3),
+        Vec3::new(1.0, 0.9, 0.0),
+        Vec3::new(1.0, 0.1, 0.0),
+    ];
+    let position = value.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let index = (position as usize).min(stops.len() - 2);
+    to_color(stops[index].lerp(&stops[index + 1], position - index as f32))
+}
+
+fn to_color(v: Vec3) -> Color {
+    let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Color::new(channel(v.x), channel(v.y), channel(v.z))
+}
@@ -0,0 +1,119 @@
+use nalgebra_glm::Vec3;
+use crate::framebuffer::{pack_pixel, unpack_pixel, AuxBuffers, Framebuffer};
+
+// Núcleo B3-spline de 5 taps del filtro A-Trous
+const KERNEL: [f32; 5] = [1.0 / 16.0, 1.0 / 4.0, 3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+// Exponente de la similitud de normales (más alto = bordes más marcados)
+const NORMAL_POWER: f32 = 64.0;
+// Diferencia relativa de profundidad tolerada entre vecinos
+const DEPTH_SIGMA: f32 = 0.05;
+// Albedo mínimo al separar la iluminación de la textura
+const MIN_ALBEDO: f32 = 0.02;
+
+// Filtro A-Trous (wavelet con huecos) guiado por los buffers auxiliares del impacto primario.
+// Se filtra la iluminación (color / albedo) para no borrar las texturas, y los pesos caen
+// a cero entre pixeles con normales o profundidades distintas.
+pub struct Denoiser {
+    pub enabled: bool,
+    pub strength: f32,     // 0 = sin efecto, 1 = filtro completo
+    pub iterations: usize, // Pasadas; el paso entre taps se duplica en cada una (1, 2, 4, ...)
+}
+
+impl Denoiser {
+    pub fn new(strength: f32) -> Self {
+        Denoiser {
+            enabled: true,
+            strength: strength.clamp(0.0, 1.0),
+            iterations: 4,
+        }
+    }
+
+    pub fn adjust_strength(&mut self, delta: f32) {
+        self.strength = (self.strength + delta).clamp(0.0, 1.0);
+    }
+
+    // Filtrar el framebuffer en su lugar; necesita los buffers auxiliares
+    pub fn apply(&self, framebuffer: &mut Framebuffer) {
+        if !self.enabled || self.strength <= 0.0 {
+            return;
+        }
+        let Some(aux) = framebuffer.aux.as_ref() else {
+            return;
+        };
+        let (width, height) = (framebuffer.width, framebuffer.height);
+
+        let original: Vec<Vec3> = framebuffer.buffer.iter().map(|&pixel| unpack_pixel(pixel)).collect();
+        let albedo: Vec<Vec3> = aux.albedo.iter().enumerate()
+            .map(|(i, a)| if aux.depth[i].is_finite() { a.sup(&Vec3::repeat(MIN_ALBEDO)) } else { Vec3::repeat(1.0) })
+            .collect();
+
+        // Iluminación sin textura
+        let mut irradiance: Vec<Vec3> = original.iter().zip(albedo.iter()).map(|(c, a)| c.component_div(a)).collect();
+
+        // La tolerancia de color crece con la fuerza y se afina en cada pasada
+        let mut color_sigma = 0.05 + self.strength * 0.45;
+        for iteration in 0..self.iterations {
+            irradiance = atrous_pass(&irradiance, aux, width, height, 1 << iteration, color_sigma);
+            color_sigma *= 0.5;
+        }
+
+        for (i, pixel) in framebuffer.buffer.iter_mut().enumerate() {
+            let filtered = irradiance[i].component_mul(&albedo[i]);
+            *pixel = pack_pixel(original[i].lerp(&filtered, self.strength));
+        }
+    }
+}
+
+// Una pasada del filtro con taps separados por `step` pixeles
+fn atrous_pass(input: &[Vec3], aux: &AuxBuffers, width: usize, height: usize, step: usize, color_sigma: f32) -> Vec<Vec3> {
+    let mut output = vec![Vec3::zeros(); input.len()];
+    let color_falloff = 1.0 / (color_sigma * color_sigma).max(1e-6);
+
+    for y in 0..height {
+        for x in 0..width {
+            let center = y * width + x;
+            let mut sum = Vec3::zeros();
+            let mut weight_sum = 0.0;
+
+            for (j, ky) in KERNEL.iter().enumerate() {
+                let sy = y as isize + (j as isize - 2) * step as isize;
+                if sy < 0 || sy >= height as isize {
+                    continue;
+                }
+                for (i, kx) in KERNEL.iter().enumerate() {
+                    let sx = x as isize + (i as isize - 2) * step as isize;
+                    if sx < 0 || sx >= width as isize {
+                        continue;
+                    }
+                    let sample = sy as usize * width + sx as usize;
+
+                    let color_distance = (input[sample] - input[center]).norm_squared();
+                    let weight = kx * ky
+                        * (-color_distance * color_falloff).exp()
+                        * geometry_weight(aux, center, sample);
+                    sum += input[sample] * weight;
+                    weight_sum += weight;
+                }
+            }
+
+            output[center] = if weight_sum > 0.0 { sum / weight_sum } else { input[center] };
+        }
+    }
+    output
+}
+
+// Semejanza geométrica entre dos pixeles según normal, profundidad y material
+fn geometry_weight(aux: &AuxBuffers, a: usize, b: usize) -> f32 {
+    let (depth_a, depth_b) = (aux.depth[a], aux.depth[b]);
+    match (depth_a.is_finite(), depth_b.is_finite()) {
+        (false, false) => return 1.0, // Ambos son fondo
+        (true, true) => {}
+        _ => return 0.0,              // Borde entre la escena y el fondo
+    }
+
+    let normal_weight = aux.normal[a].dot(&aux.normal[b]).max(0.0).powf(NORMAL_POWER);
+    let relative_depth = (depth_a - depth_b).abs() / (depth_a * DEPTH_SIGMA).max(1e-4);
+    let depth_weight = (-relative_depth).exp();
+    let material_weight = if aux.material_id[a] == aux.material_id[b] { 1.0 } else { 0.5 };
+    normal_weight * depth_weight * material_weight
+}
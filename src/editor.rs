@@ -0,0 +1,186 @@
+use nalgebra_glm::Vec3;
+use crate::color::Color;
+use crate::cube::Cube;
+use crate::framebuffer::Framebuffer;
+use crate::material::Material;
+use std::sync::Arc;
+
+// Cambio hecho por el editor; deshacer aplica el cambio inverso
+#[derive(Clone)]
+pub enum EditAction {
+    Placed(Cube),
+    Removed(Cube),
+}
+
+// Efecto de una edición sobre la lista de bloques, para que lo que apunta a los bloques por
+// índice (las pistas de animación) se pueda actualizar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockEdit {
+    Added,          // Se agregó un bloque al final de la lista
+    Removed(usize), // Se quitó el bloque de ese índice
+}
+
+const SLOT_SIZE: usize = 40;
+const SLOT_GAP: usize = 4;
+const HOTBAR_MARGIN: usize = 10;
+
+// Editor de bloques dentro de la ventana: quitar y poner cubos alineados a la cuadrícula
+pub struct Editor {
+    pub enabled: bool,
+    pub hotbar: Vec<Arc<Material>>, // Materiales que se pueden colocar
+    pub selected: usize,            // Ranura activa de la barra
+    swatches: Vec<Color>,           // Color representativo de cada material (promedio de la textura)
+    undo_stack: Vec<EditAction>,
+    redo_stack: Vec<EditAction>,
+}
+
+impl Editor {
+    pub fn new(hotbar: Vec<Arc<Material>>) -> Self {
+        let swatches = hotbar.iter().map(|material| swatch(material)).collect();
+        Editor {
+            enabled: false,
+            hotbar,
+            selected: 0,
+            swatches,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+
+    pub fn active_material(&self) -> Arc<Material> {
+        self.hotbar[self.selected].clone()
+    }
+
+    pub fn select(&mut self, slot: usize) {
+        if slot < self.hotbar.len() {
+            self.selected = slot;
+        }
+    }
+
+    // Pasar a la ranura siguiente, volviendo a la primera después de la última
+    pub fn next_slot(&mut self) {
+        if !self.hotbar.is_empty() {
+            self.selected = (self.selected + 1) % self.hotbar.len();
+        }
+    }
+
+    // Quitar el bloque `index`
+    pub fn remove(&mut self, blocks: &mut Vec<Cube>, index: usize) -> Option<BlockEdit> {
+        if index >= blocks.len() {
+            return None;
+        }
+        let block = blocks.remove(index);
+        self.record(EditAction::Removed(block));
+        Some(BlockEdit::Removed(index))
+    }
+
+    // Poner un bloque del material activo pegado a la cara impactada en `point` con normal `normal`.
+    // El centro se alinea a la cuadrícula de lado `size`; no se pone nada si la celda está ocupada.
+    pub fn place(&mut self, blocks: &mut Vec<Cube>, point: Vec3, normal: Vec3, size: f32) -> Option<BlockEdit> {
+        let center = ((point + normal * (size / 2.0)) / size).map(|v| v.round()) * size;
+        if find_block(blocks, center, size).is_some() {
+            return None;
+        }
+        let block = Cube::new(center, size, self.active_material());
+        blocks.push(block.clone());
+        self.record(EditAction::Placed(block));
+        Some(BlockEdit::Added)
+    }
+
+    // Deshacer y rehacer devuelven None si no había nada que cambiar
+    pub fn undo(&mut self, blocks: &mut Vec<Cube>) -> Option<BlockEdit> {
+        let action = self.undo_stack.pop()?;
+        let edit = revert(blocks, &action);
+        self.redo_stack.push(action);
+        edit
+    }
+
+    pub fn redo(&mut self, blocks: &mut Vec<Cube>) -> Option<BlockEdit> {
+        let action = self.redo_stack.pop()?;
+        let edit = apply(blocks, &action);
+        self.undo_stack.push(action);
+        edit
+    }
+
+    // Olvidar lo que se podía deshacer y rehacer, por ejemplo cuando la física movió o quitó bloques
+    // y las acciones guardadas ya no corresponden a la maqueta
+    pub fn clear_history(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    // Un cambio nuevo invalida lo que se podía rehacer
+    fn record(&mut self, action: EditAction) {
+        self.undo_stack.push(action);
+        self.redo_stack.clear();
+    }
+
+    // Barra de materiales centrada abajo; la ranura activa lleva borde blanco
+    pub fn draw_hotbar(&self, framebuffer: &mut Framebuffer) {
+        if !self.enabled || self.hotbar.is_empty() {
+            return;
+        }
+        let slots = self.hotbar.len();
+        let total_width = slots * SLOT_SIZE + (slots - 1) * SLOT_GAP;
+        let left = framebuffer.width.saturating_sub(total_width) / 2;
+        let top = framebuffer.height.saturating_sub(SLOT_SIZE + HOTBAR_MARGIN);
+
+        for (slot, color) in self.swatches.iter().enumerate() {
+            let x = (left + slot * (SLOT_SIZE + SLOT_GAP)) as isize;
+            let border = if slot == self.selected { Color::new(255, 255, 255) } else { Color::new(30, 30, 30) };
+            framebuffer.set_current_color(border);
+            framebuffer.fill_rect(x, top as isize, SLOT_SIZE, SLOT_SIZE);
+            framebuffer.set_current_color(*color);
+            framebuffer.fill_rect(x + 3, top as isize + 3, SLOT_SIZE - 6, SLOT_SIZE - 6);
+
+            // Tecla de la ranura: 1..9 y 0 para la décima; a las siguientes se llega con Tab
+            if slot < 10 {
+                framebuffer.set_current_color(Color::new(255, 255, 255));
+                framebuffer.draw_text(x + 5, top as isize + 5, &((slot + 1) % 10).to_string(), 1);
+            }
+        }
+    }
+}
+
+// Índice del bloque cuyo centro coincide con `center`
+pub fn find_block(blocks: &[Cube], center: Vec3, size: f32) -> Option<usize> {
+    blocks.iter().position(|block| (block.position() - center).magnitude() < size * 0.25)
+}
+
+fn apply(blocks: &mut Vec<Cube>, action: &EditAction) -> Option<BlockEdit> {
+    match action {
+        EditAction::Placed(block) => {
+            blocks.push(block.clone());
+            Some(BlockEdit::Added)
+        }
+        EditAction::Removed(block) => {
+            let index = find_block(blocks, block.position(), block.size)?;
+            blocks.remove(index);
+            Some(BlockEdit::Removed(index))
+        }
+    }
+}
+
+fn revert(blocks: &mut Vec<Cube>, action: &EditAction) -> Option<BlockEdit> {
+    match action {
+        EditAction::Placed(block) => apply(blocks, &EditAction::Removed(block.clone())),
+        EditAction::Removed(block) => apply(blocks, &EditAction::Placed(block.clone())),
+    }
+}
+
+// Color promedio de la textura del material (o su color difuso si no tiene)
+fn swatch(material: &Material) -> Color {
+    match material.texture.as_ref().filter(|_| material.has_texture) {
+        Some(texture) if !texture.data.is_empty() => {
+            let mut sum = [0u64; 3];
+            for color in &texture.data {
+                sum[0] += color.r as u64;
+                sum[1] += color.g as u64;
+                sum[2] += color.b as u64;
+            }
+            let n = texture.data.len() as u64;
+            Color::new((sum[0] / n) as u8, (sum[1] / n) as u8, (sum[2] / n) as u8)
+        }
+        _ => material.diffuse,
+    }
+}
@@ -0,0 +1,170 @@
+use nalgebra_glm::Vec3;
+use crate::cube::Cube;
+use crate::grid::{Grid3D, GridObject};
+use crate::material::Material;
+use std::sync::Arc;
+
+// Nivel de una celda llena (fuente o agua que cae); cada celda de avance lateral pierde uno
+pub const WATER_LEVELS: u8 = 8;
+// Pasos del reloj entre actualizaciones del agua (a 60 pasos por segundo, 10 por segundo)
+const STEPS_PER_UPDATE: u32 = 6;
+// Celdas libres alrededor de los bloques para que el agua pueda salirse de la maqueta
+const MARGIN: usize = 3;
+// Tope de actualizaciones al dejar que el agua se asiente de una vez
+const MAX_SETTLE_UPDATES: usize = 1000;
+
+const NEIGHBOURS: [(isize, isize); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+// Agua celular al estilo de Minecraft sobre un Grid3D: las fuentes no cambian, el agua cae
+// mientras haya aire debajo y se extiende de lado sobre suelo firme perdiendo un nivel por celda.
+// Las celdas que ya no reciben agua se secan.
+pub struct WaterSim {
+    pub grid: Grid3D,
+    pub settled: bool, // La última actualización no cambió nada
+    step_counter: u32,
+}
+
+impl WaterSim {
+    // Grid que cubre los bloques sólidos y las fuentes, alineado a sus centros
+    pub fn new(solids: &[Cube], sources: &[Cube], cell_size: f32) -> Self {
+        let centers = || solids.iter().chain(sources.iter()).map(|cube| cube.position());
+        let mut min = centers().fold(Vec3::repeat(f32::INFINITY), |acc, p| acc.inf(&p));
+        let mut max = centers().fold(Vec3::repeat(f32::NEG_INFINITY), |acc, p| acc.sup(&p));
+        if min.x > max.x {
+            min = Vec3::zeros();
+            max = Vec3::zeros();
+        }
+        let extent = ((max - min) / cell_size).map(|c| c.round() as usize).max();
+        let size = extent + 1 + MARGIN * 2;
+        let origin = min - Vec3::repeat(MARGIN as f32 * cell_size);
+
+        let mut sim = WaterSim {
+            grid: Grid3D::placed(size, origin, cell_size),
+            settled: false,
+            step_counter: 0,
+        };
+        for source in sources {
+            if let Some((x, y, z)) = sim.grid.cell_of(source.position()) {
+                sim.grid.place_object(x, y, z, GridObject::Water { level: WATER_LEVELS, source: true });
+            }
+        }
+        sim.set_solids(solids);
+        sim
+    }
+
+    // Volver a marcar las celdas sólidas (por ejemplo, después de editar bloques).
+    // Un bloque puesto sobre agua la reemplaza.
+    pub fn set_solids(&mut self, solids: &[Cube]) {
+        let size = self.grid.size();
+        for x in 0..size {
+            for y in 0..size {
+                for z in 0..size {
+                    if *self.grid.get_object(x, y, z) == GridObject::Cube {
+                        self.grid.place_object(x, y, z, GridObject::Empty);
+                    }
+                }
+            }
+        }
+        for solid in solids {
+            if let Some((x, y, z)) = self.grid.cell_of(solid.position()) {
+                self.grid.place_object(x, y, z, GridObject::Cube);
+            }
+        }
+        self.settled = false;
+    }
+
+    // Un paso del reloj de la simulación; el agua solo se actualiza cada STEPS_PER_UPDATE pasos
+    pub fn step(&mut self) {
+        self.step_counter += 1;
+        if self.step_counter >= STEPS_PER_UPDATE {
+            self.step_counter = 0;
+            self.settled = !self.update();
+        }
+    }
+
+    // Actualizar hasta que el agua deje de cambiar, sin esperar al reloj
+    pub fn settle(&mut self) {
+        for _ in 0..MAX_SETTLE_UPDATES {
+            if !self.update() {
+                break;
+            }
+        }
+        self.settled = true;
+    }
+
+    // Calcular el nuevo estado de todas las celdas a partir del anterior. Devuelve si algo cambió.
+    fn update(&mut self) -> bool {
+        let size = self.grid.size();
+        let mut changes = Vec::new();
+        for x in 0..size {
+            for y in 0..size {
+                for z in 0..size {
+                    let current = *self.grid.get_object(x, y, z);
+                    let next = self.next_state(x as isize, y as isize, z as isize, current);
+                    if next != current {
+                        changes.push((x, y, z, next));
+                    }
+                }
+            }
+        }
+
+        let changed = !changes.is_empty();
+        for (x, y, z, object) in changes {
+            self.grid.place_object(x, y, z, object);
+        }
+        changed
+    }
+
+    fn next_state(&self, x: isize, y: isize, z: isize, current: GridObject) -> GridObject {
+        match current {
+            GridObject::Empty | GridObject::Water { source: false, .. } => {}
+            _ => return current, // Sólidos y fuentes no cambian
+        }
+
+        // El agua que cae llena la celda
+        if let Some(GridObject::Water { .. }) = self.grid.get(x, y + 1, z) {
+            return GridObject::Water { level: WATER_LEVELS, source: false };
+        }
+
+        // El agua de una celda vecina solo se extiende de lado si descansa sobre un bloque o una
+        // fuente; el agua que cae no sostiene a nada, así la cascada no se abre en una lámina
+        let mut level = 0;
+        for (dx, dz) in NEIGHBOURS {
+            if let Some(GridObject::Water { level: neighbour, .. }) = self.grid.get(x + dx, y, z + dz) {
+                let supported = matches!(
+                    self.grid.get(x + dx, y - 1, z + dz),
+                    Some(GridObject::Cube) | Some(GridObject::Water { source: true, .. })
+                );
+                if supported {
+                    level = level.max(neighbour - 1);
+                }
+            }
+        }
+
+        if level > 0 {
+            GridObject::Water { level, source: false }
+        } else {
+            GridObject::Empty
+        }
+    }
+
+    // Cubos para render del agua, en orden estable de celdas. Las celdas parciales son cajas
+    // más bajas; una celda con agua encima se dibuja llena para que la cascada no tenga huecos.
+    pub fn cubes(&self, material: &Arc<Material>) -> Vec<Cube> {
+        let size = self.grid.size();
+        let mut cubes = Vec::new();
+        for x in 0..size {
+            for y in 0..size {
+                for z in 0..size {
+                    if let GridObject::Water { level, .. } = *self.grid.get_object(x, y, z) {
+                        let covered = matches!(self.grid.get(x as isize, y as isize + 1, z as isize), Some(GridObject::Water { .. }));
+                        let mut cube = Cube::new(self.grid.cell_center(x, y, z), self.grid.cell_size, material.clone());
+                        cube.height = if covered { 1.0 } else { level as f32 / WATER_LEVELS as f32 };
+                        cubes.push(cube);
+                    }
+                }
+            }
+        }
+        cubes
+    }
+}
@@ -1,11 +1,84 @@
 use crate::color::Color;
-use minifb::{Window, WindowOptions, Key};
+use crate::font::{glyph, ADVANCE_X, ADVANCE_Y, GLYPH_HEIGHT, GLYPH_WIDTH};
+use crate::material::NO_MATERIAL_ID;
+use crate::ray_intersect::Intersect;
+use nalgebra_glm::Vec3;
+use std::path::Path;
 
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
     pub buffer: Vec<u32>, // El buffer se representa como un vector de u32 para los colores
     current_color: u32,    // Agrega un campo para almacenar el color actual
+    pub aux: Option<AuxBuffers>, // Buffers auxiliares del impacto primario (opcionales)
+}
+
+// Datos del impacto primario de cada pixel, para composición y eliminación de ruido.
+// Los pixeles sin impacto quedan con distancia infinita, normal/albedo/UV en cero e IDs en NO_HIT.
+pub struct AuxBuffers {
+    pub depth: Vec<f32>,         // Distancia del ojo al impacto
+    pub normal: Vec<Vec3>,       // Normal en el espacio del mundo
+    pub albedo: Vec<Vec3>,       // Color base de la superficie, en [0, 1]
+    pub material_id: Vec<u32>,
+    pub object_id: Vec<u32>,     // Identificador estable del bloque (el de `object_ids` en render); NO_HIT para las partículas
+    pub uv: Vec<(f32, f32)>,
+}
+
+pub const NO_HIT: u32 = u32::MAX;
+
+// Pixel 0xAARRGGBB a color flotante en [0, 1]
+pub fn unpack_pixel(pixel: u32) -> Vec3 {
+    Vec3::new(((pixel >> 16) & 0xff) as f32, ((pixel >> 8) & 0xff) as f32, (pixel & 0xff) as f32) / 255.0
+}
+
+// Color flotante (se recorta a [0, 1]) a pixel opaco 0xAARRGGBB
+pub fn pack_pixel(color: Vec3) -> u32 {
+    let channel = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u32;
+    (255 << 24) | (channel(color.x) << 16) | (channel(color.y) << 8) | channel(color.z)
+}
+
+impl AuxBuffers {
+    pub fn new(size: usize) -> Self {
+        AuxBuffers {
+            depth: vec![f32::INFINITY; size],
+            normal: vec![Vec3::zeros(); size],
+            albedo: vec![Vec3::zeros(); size],
+            material_id: vec![NO_HIT; size],
+            object_id: vec![NO_HIT; size],
+            uv: vec![(0.0, 0.0); size],
+        }
+    }
+
+    // Volver todos los pixeles al estado "sin impacto"
+    pub fn clear(&mut self) {
+        *self = AuxBuffers::new(self.depth.len());
+    }
+
+    // Guardar los datos de un impacto primario en el pixel `index`. El objeto queda con su índice en
+    // la lista renderizada hasta que render lo traduce con map_object_ids.
+    pub fn record(&mut self, index: usize, object_index: Option<usize>, intersect: &Intersect) {
+        if !intersect.is_intersecting {
+            return;
+        }
+        let albedo = intersect.material.get_diffuse_color(intersect.u, intersect.v);
+        self.depth[index] = intersect.distance;
+        self.normal[index] = intersect.normal;
+        self.albedo[index] = Vec3::new(albedo.r as f32, albedo.g as f32, albedo.b as f32) / 255.0;
+        // Los materiales fuera de la lista (partículas) quedan sin ID, como el fondo
+        self.material_id[index] = match intersect.material.id {
+            NO_MATERIAL_ID => NO_HIT,
+            id => id as u32,
+        };
+        self.object_id[index] = object_index.map_or(NO_HIT, |i| i as u32);
+        self.uv[index] = (intersect.u, intersect.v);
+    }
+
+    // Reemplazar los índices en la lista renderizada por los identificadores estables `ids`
+    pub fn map_object_ids(&mut self, ids: &[usize]) {
+        for id in self.object_id.iter_mut().filter(|id| **id != NO_HIT) {
+            *id = ids.get(*id as usize).map_or(NO_HIT, |&stable| stable as u32);
+        }
+    }
 }
 
 impl Framebuffer {
@@ -15,15 +88,13 @@ impl Framebuffer {
             height,
             buffer: vec![0; width * height], // Inicializar el buffer con color negro
             current_color: 0,                // Color inicial
+            aux: None,
         }
     }
 
-    // Método para limpiar el framebuffer con un color de fondo
-    pub fn clear(&mut self, color: Color) {
-        let color_u32 = (255 << 24) | ((color.r as u32) << 16) | ((color.g as u32) << 8) | (color.b as u32);
-        for pixel in self.buffer.iter_mut() {
-            *pixel = color_u32;
-        }
+    // Activar los buffers auxiliares; render los llena con cada impacto primario
+    pub fn enable_aux(&mut self) {
+        self.aux = Some(AuxBuffers::new(self.width * self.height));
     }
 
     // Método para dibujar un punto en el framebuffer
@@ -39,21 +110,76 @@ impl Framebuffer {
         self.current_color = (255 << 24) | ((color.r as u32) << 16) | ((color.g as u32) << 8) | (color.b as u32);
     }
 
-    // Método para renderizar la ventana utilizando minifb
-    pub fn render_window(&self) {
-        let mut window = Window::new(
-            "Framebuffer Example",
-            self.width,
-            self.height,
-            WindowOptions::default(),
-        )
-        .unwrap_or_else(|e| {
-            panic!("{}", e);
-        });
+    // Rellenar un rectángulo con el color actual (se recorta a los bordes)
+    pub fn fill_rect(&mut self, x: isize, y: isize, width: usize, height: usize) {
+        for py in y..y + height as isize {
+            for px in x..x + width as isize {
+                self.point(px, py);
+            }
+        }
+    }
 
-        // Mientras la ventana esté abierta y no se presione la tecla ESC
-        while window.is_open() && !window.is_key_down(Key::Escape) {
-            window.update_with_buffer(&self.buffer, self.width, self.height).unwrap();
+    // Oscurecer un rectángulo multiplicando su color por `factor`, como fondo de texto
+    pub fn darken_rect(&mut self, x: usize, y: usize, width: usize, height: usize, factor: f32) {
+        for py in y..(y + height).min(self.height) {
+            for px in x..(x + width).min(self.width) {
+                let index = py * self.width + px;
+                self.buffer[index] = pack_pixel(unpack_pixel(self.buffer[index]) * factor);
+            }
         }
     }
+
+    // Escribir texto con la fuente de mapa de bits y el color actual; `scale` agranda cada pixel.
+    // Los saltos de línea bajan una fila de texto.
+    pub fn draw_text(&mut self, x: isize, y: isize, text: &str, scale: usize) {
+        let scale = scale.max(1);
+        let mut cursor_x = x;
+        let mut cursor_y = y;
+        for c in text.chars() {
+            if c == '\n' {
+                cursor_x = x;
+                cursor_y += (ADVANCE_Y * scale) as isize;
+                continue;
+            }
+            for (row, bits) in glyph(c).iter().enumerate() {
+                for column in 0..GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+                        let px = cursor_x + (column * scale) as isize;
+                        let py = cursor_y + (row * scale) as isize;
+                        self.fill_rect(px, py, scale, scale);
+                    }
+                }
+            }
+            cursor_x += (ADVANCE_X * scale) as isize;
+        }
+    }
+
+    // Tamaño en pixeles de un texto de una línea dibujado con draw_text
+    pub fn text_size(text: &str, scale: usize) -> (usize, usize) {
+        (text.chars().count() * ADVANCE_X * scale.max(1), GLYPH_HEIGHT * scale.max(1))
+    }
+
+    // Copiar otro framebuffer con su esquina superior izquierda en (x, y)
+    pub fn blit(&mut self, source: &Framebuffer, x: usize, y: usize) {
+        for row in 0..source.height.min(self.height.saturating_sub(y)) {
+            let columns = source.width.min(self.width.saturating_sub(x));
+            let src_start = row * source.width;
+            let dst_start = (y + row) * self.width + x;
+            self.buffer[dst_start..dst_start + columns].copy_from_slice(&source.buffer[src_start..src_start + columns]);
+        }
+    }
+
+    // Copia del buffer como imagen RGB de 8 bits
+    pub fn to_rgb_image(&self) -> image::RgbImage {
+        image::RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let pixel = self.buffer[y as usize * self.width + x as usize];
+            image::Rgb([(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8])
+        })
+    }
+
+    // Guardar el contenido del framebuffer como imagen (el formato sale de la extensión)
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        self.to_rgb_image().save(path.as_ref())
+            .map_err(|e| format!("No se pudo guardar {}: {}", path.as_ref().display(), e))
+    }
 }
@@ -11,23 +11,27 @@ impl Frustum {
         // Obtener la matriz de vista/proyección de la cámara
         let view_proj = camera.get_view_projection_matrix();
 
-        // Extraer los planos del frustum
-        let left = view_proj.column(3) + view_proj.column(0);
-        let right = view_proj.column(3) - view_proj.column(0);
-        let top = view_proj.column(3) - view_proj.column(1);
-        let bottom = view_proj.column(3) + view_proj.column(1);
-        let near = view_proj.column(3) + view_proj.column(2);
-        let far = view_proj.column(3) - view_proj.column(2);
+        // Extraer los planos del frustum a partir de las filas de la matriz (Gribb-Hartmann)
+        let row = |i: usize| -> Vec4 { view_proj.row(i).transpose() };
+        let left = row(3) + row(0);
+        let right = row(3) - row(0);
+        let top = row(3) - row(1);
+        let bottom = row(3) + row(1);
+        let near = row(3) + row(2);
+        let far = row(3) - row(2);
+
+        // Normalizar para que la ecuación del plano dé distancias en unidades del mundo
+        let normalize = |plane: Vec4| -> Vec4 { plane / plane.xyz().magnitude() };
 
         Frustum {
-            planes: [left, right, top, bottom, near, far],
+            planes: [left, right, top, bottom, near, far].map(normalize),
         }
     }
 
     // Verifica si una esfera (definida por su centro y radio) está dentro del frustum
     pub fn is_sphere_in_frustum(&self, center: Vec3, radius: f32) -> bool {
         for plane in &self.planes {
-            let distance = (plane.x * center.x + plane.y * center.y + plane.z * center.z + plane.w) * 19.0;
+            let distance = plane.x * center.x + plane.y * center.y + plane.z * center.z + plane.w;
             if distance < -radius {
                 return false; // Si está completamente fuera de uno de los planos, está fuera del frustum
             }
@@ -1,17 +1,19 @@
 use nalgebra_glm::Vec3;
 
 // Define el tipo de objeto que puede haber en el grid. Por ahora, lo dejamos como un enum.
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum GridObject {
     Empty,      // Representa una celda vacía
     Cube,       // Representa un cubo
-    Sphere,     // Representa una esfera
+    Water { level: u8, source: bool }, // Agua con su nivel (1 = charco, el máximo = celda llena)
     // Otros objetos pueden agregarse aquí
 }
 
 pub struct Grid3D {
     size: usize,                         // Tamaño del grid (10x10x10)
     cells: Vec<Vec<Vec<GridObject>>>,    // Vec anidado para representar cada celda del grid
+    pub origin: Vec3,                    // Centro de la celda (0, 0, 0) en el mundo
+    pub cell_size: f32,                  // Lado de cada celda en el mundo
 }
 
 impl Grid3D {
@@ -21,6 +23,47 @@ impl Grid3D {
         Grid3D {
             size,
             cells: empty_grid,
+            origin: Vec3::zeros(),
+            cell_size: 1.0,
+        }
+    }
+
+    // Grid colocado en el mundo: la celda (0, 0, 0) centrada en `origin` y celdas de lado `cell_size`
+    pub fn placed(size: usize, origin: Vec3, cell_size: f32) -> Self {
+        Grid3D {
+            origin,
+            cell_size,
+            ..Grid3D::new(size)
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // Celda que contiene un punto del mundo, si cae dentro del grid
+    pub fn cell_of(&self, point: Vec3) -> Option<(usize, usize, usize)> {
+        let cell = ((point - self.origin) / self.cell_size).map(|c| c.round());
+        let in_range = |c: f32| c >= 0.0 && (c as usize) < self.size;
+        if in_range(cell.x) && in_range(cell.y) && in_range(cell.z) {
+            Some((cell.x as usize, cell.y as usize, cell.z as usize))
+        } else {
+            None
+        }
+    }
+
+    // Centro de la celda (x, y, z) en el mundo
+    pub fn cell_center(&self, x: usize, y: usize, z: usize) -> Vec3 {
+        self.origin + Vec3::new(x as f32, y as f32, z as f32) * self.cell_size
+    }
+
+    // Como get_object, pero con coordenadas con signo y sin avisar: None fuera del grid
+    pub fn get(&self, x: isize, y: isize, z: isize) -> Option<GridObject> {
+        let size = self.size as isize;
+        if (0..size).contains(&x) && (0..size).contains(&y) && (0..size).contains(&z) {
+            Some(self.cells[x as usize][y as usize][z as usize])
+        } else {
+            None
         }
     }
 
@@ -42,21 +85,4 @@ impl Grid3D {
             &GridObject::Empty
         }
     }
-
-    // Método para imprimir el estado del grid (para depuración)
-    pub fn print_grid(&self) {
-        for x in 0..self.size {
-            for y in 0..self.size {
-                for z in 0..self.size {
-                    match self.get_object(x, y, z) {
-                        GridObject::Empty => print!("[ ]"),
-                        GridObject::Cube => print!("[C]"),
-                        GridObject::Sphere => print!("[S]"),
-                    }
-                }
-                println!(); // Salto de línea después de cada fila
-            }
-            println!("---"); // Separador entre capas del eje Z
-        }
-    }
 }
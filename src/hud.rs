@@ -0,0 +1,107 @@
+use crate::color::Color;
+use crate::font::ADVANCE_Y;
+use crate::framebuffer::Framebuffer;
+use std::collections::VecDeque;
+
+// Cuadros que guarda la gráfica de tiempos
+const GRAPH_SAMPLES: usize = 120;
+const GRAPH_HEIGHT: usize = 60;
+// Tiempo que llena la gráfica (o más, si hubo cuadros más lentos)
+const GRAPH_MIN_RANGE: f32 = 1.0 / 20.0;
+const MARGIN: usize = 8;
+const TEXT_SCALE: usize = 2;
+
+// Panel de información superpuesto a la imagen
+pub struct Hud {
+    pub enabled: bool,
+    frame_times: VecDeque<f32>, // Segundos de los últimos cuadros, del más viejo al más nuevo
+    pub target_frame_time: f32, // Se marca como línea de referencia en la gráfica
+}
+
+impl Hud {
+    pub fn new(target_frame_time: f32) -> Self {
+        Hud {
+            enabled: false,
+            frame_times: VecDeque::with_capacity(GRAPH_SAMPLES),
+            target_frame_time,
+        }
+    }
+
+    pub fn record_frame(&mut self, frame_time: f32) {
+        if self.frame_times.len() == GRAPH_SAMPLES {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+    }
+
+    // Dibujar las líneas de texto arriba a la izquierda y la gráfica de tiempos debajo
+    pub fn draw(&self, framebuffer: &mut Framebuffer, lines: &[String]) {
+        if !self.enabled {
+            return;
+        }
+
+        // El panel deja lugar debajo del texto para la gráfica
+        let (width, height) = panel_size(lines);
+        let graph_top = height;
+        framebuffer.darken_rect(0, 0, width.max(GRAPH_SAMPLES * 2 + MARGIN * 2), height + GRAPH_HEIGHT + MARGIN, 0.35);
+        draw_lines(framebuffer, 0, 0, lines);
+        self.draw_graph(framebuffer, MARGIN, graph_top);
+    }
+
+    // Una barra de 2 pixeles por cuadro; verde bajo el objetivo, amarilla hasta el doble, roja después
+    fn draw_graph(&self, framebuffer: &mut Framebuffer, left: usize, top: usize) {
+        let range = self.frame_times.iter().cloned().fold(GRAPH_MIN_RANGE, f32::max);
+        let bottom = (top + GRAPH_HEIGHT) as isize;
+
+        for (i, &frame_time) in self.frame_times.iter().enumerate() {
+            let bar = ((frame_time / range) * GRAPH_HEIGHT as f32).round().clamp(1.0, GRAPH_HEIGHT as f32) as usize;
+            let color = if frame_time <= self.target_frame_time {
+                Color::new(80, 220, 80)
+            } else if frame_time <= self.target_frame_time * 2.0 {
+                Color::new(230, 200, 60)
+            } else {
+                Color::new(230, 70, 60)
+            };
+            framebuffer.set_current_color(color);
+            framebuffer.fill_rect((left + i * 2) as isize, bottom - bar as isize, 2, bar);
+        }
+
+        // Línea del tiempo objetivo
+        let target = ((self.target_frame_time / range) * GRAPH_HEIGHT as f32).round() as isize;
+        framebuffer.set_current_color(Color::new(255, 255, 255));
+        framebuffer.fill_rect(left as isize, bottom - target, GRAPH_SAMPLES * 2, 1);
+    }
+}
+
+// Tamaño del panel para unas líneas de texto, con márgenes
+pub fn panel_size(lines: &[String]) -> (usize, usize) {
+    let text_width = lines.iter()
+        .map(|line| Framebuffer::text_size(line, TEXT_SCALE).0)
+        .max()
+        .unwrap_or(0);
+    (text_width + MARGIN * 2, lines.len() * ADVANCE_Y * TEXT_SCALE + MARGIN * 2)
+}
+
+// Panel de texto con fondo oscurecido y esquina superior izquierda en (left, top)
+pub fn draw_panel(framebuffer: &mut Framebuffer, left: usize, top: usize, lines: &[String]) {
+    let (width, height) = panel_size(lines);
+    framebuffer.darken_rect(left, top, width, height, 0.35);
+    draw_lines(framebuffer, left, top, lines);
+}
+
+fn draw_lines(framebuffer: &mut Framebuffer, left: usize, top: usize, lines: &[String]) {
+    framebuffer.set_current_color(Color::new(255, 255, 255));
+    for (i, line) in lines.iter().enumerate() {
+        let y = top + MARGIN + i * ADVANCE_Y * TEXT_SCALE;
+        framebuffer.draw_text((left + MARGIN) as isize, y as isize, line, TEXT_SCALE);
+    }
+}
+
+// Cantidad abreviada para el panel (1234567 -> "1.2M")
+pub fn short_count(value: u64) -> String {
+    match value {
+        0..=9_999 => value.to_string(),
+        10_000..=999_999 => format!("{:.1}K", value as f32 / 1e3),
+        _ => format!("{:.1}M", value as f32 / 1e6),
+    }
+}
@@ -1,10 +1,13 @@
 use nalgebra_glm::Vec3;
 use crate::color::Color;
+use crate::sky::Sky;
 
+#[derive(Clone)]
 pub struct Light {
     pub position: Vec3,
     pub color: Color,
     pub intensity: f32,
+    pub sky: Sky, // Fondo y luz ambiental que acompañan a esta luz
 }
 
 impl Light {
@@ -13,6 +16,7 @@ impl Light {
             position,
             color,
             intensity,
+            sky: Sky::flat(Color::new(135, 206, 235), Color::new(255, 255, 255) * 0.3),
         }
     }
 }
\ No newline at end of file
@@ -9,26 +9,66 @@ mod grid;
 mod light;
 mod cast_ray;
 mod frustum;
+mod scene;
+mod camera_path;
+mod output;
+mod turntable;
+mod denoise;
+mod accumulate;
+mod resolution;
+mod postprocess;
+mod font;
+mod stats;
+mod hud;
+mod debug_view;
+mod picking;
+mod editor;
+mod selection;
+mod animation;
+mod clock;
+mod sky;
+mod day_cycle;
+mod fluid;
+mod sphere;
+mod billboard;
+mod particles;
+mod physics;
 
 use framebuffer::Framebuffer;
-use render::render;
-use camera::Camera;
+use render::{render, center_intersect, RenderSettings};
+use camera::{Camera, CameraMode, Projection, StereoMode, ViewPreset};
 use material::{Material, Texture};
 use color::Color;
 use nalgebra_glm::{Vec3, vec3};
 use cube::Cube;
-use grid::{Grid3D, GridObject};
 use light::Light;
+use camera_path::{CameraPath, Interpolation};
+use turntable::{Turntable, scene_bounds};
+use output::{save_aux_buffers, AuxFormat, FrameSink};
 use frustum::Frustum;
-use once_cell::sync::OnceCell;
-use minifb::{Key, Window, WindowOptions};
+use denoise::Denoiser;
+use accumulate::{view_signature, Accumulator};
+use resolution::{upscale, ResolutionController, UpscaleFilter};
+use postprocess::{Effect, Lut, PostProcess};
+use hud::{draw_panel, panel_size, short_count, Hud};
+use picking::{pick, Pick};
+use debug_view::DebugView;
+use editor::{BlockEdit, Editor};
+use selection::Selection;
+use animation::Animation;
+use clock::{SimulationClock, STEPS_PER_SECOND};
+use day_cycle::DayCycle;
+use fluid::WaterSim;
+use particles::ParticleSystem;
+use physics::BlockPhysics;
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
 use std::sync::Arc;
 use std::time::Instant;
 use crate::ray_intersect::RayIntersect;
 
 // Crear el método para cargar texturas
 fn load_texture(path: &str) -> Arc<Texture> {
-    Texture::load_from_file(path).into()
+    Texture::load_from_file(path)
 }
 
 
@@ -44,6 +84,10 @@ fn define_materials() -> Vec<Arc<Material>> {
             transparency: 0.1,  // Moderada transparencia
             texture: Some(load_texture("C:/Users/irvin/UVG/Sexto_Semestre/Graficas/release/textures/moss (1).png")),
             has_texture: true,
+            id: 0,
+            name: "moss",
+            emission: 0.0,
+            falls: false,
         }),
         // Dirt
         Arc::new(Material {
@@ -54,6 +98,10 @@ fn define_materials() -> Vec<Arc<Material>> {
             transparency: 0.4,
             texture: Some(load_texture("C:/Users/irvin/UVG/Sexto_Semestre/Graficas/release/textures/dirt.png")),
             has_texture: true,
+            id: 1,
+            name: "dirt",
+            emission: 0.0,
+            falls: false,
         }),
         // Stone
         Arc::new(Material {
@@ -64,6 +112,10 @@ fn define_materials() -> Vec<Arc<Material>> {
             transparency: 0.1,
             texture: Some(load_texture("C:/Users/irvin/UVG/Sexto_Semestre/Graficas/release/textures/stone.png")),
             has_texture: true,
+            id: 2,
+            name: "stone",
+            emission: 0.0,
+            falls: false,
         }),
         // Redstone
         Arc::new(Material {
@@ -74,6 +126,10 @@ fn define_materials() -> Vec<Arc<Material>> {
             transparency: 0.1,
             texture: Some(load_texture("C:/Users/irvin/UVG/Sexto_Semestre/Graficas/release/textures/redstone.png")),
             has_texture: true,
+            id: 3,
+            name: "redstone",
+            emission: 0.4, // La redstone brilla: de noche domina sobre la luz ambiental
+            falls: false,
         }),
         // Violetstone
         Arc::new(Material {
@@ -84,6 +140,10 @@ fn define_materials() -> Vec<Arc<Material>> {
             transparency: 0.1,
             texture: Some(load_texture("C:/Users/irvin/UVG/Sexto_Semestre/Graficas/release/textures/violetstone.png")),
             has_texture: true,
+            id: 4,
+            name: "violetstone",
+            emission: 0.0,
+            falls: false,
         }),
         // Blackstone
         Arc::new(Material {
@@ -94,6 +154,10 @@ fn define_materials() -> Vec<Arc<Material>> {
             transparency: 0.0,
             texture: Some(load_texture("C:/Users/irvin/UVG/Sexto_Semestre/Graficas/release/textures/blackstone.png")),
             has_texture: true,
+            id: 5,
+            name: "blackstone",
+            emission: 0.0,
+            falls: false,
         }),
         // Wood
         Arc::new(Material {
@@ -104,6 +168,10 @@ fn define_materials() -> Vec<Arc<Material>> {
             transparency: 0.5,
             texture: Some(load_texture("C:/Users/irvin/UVG/Sexto_Semestre/Graficas/release/textures/wood.png")),
             has_texture: true,
+            id: 6,
+            name: "wood",
+            emission: 0.0,
+            falls: false,
         }),
         // Door
         Arc::new(Material {
@@ -114,6 +182,10 @@ fn define_materials() -> Vec<Arc<Material>> {
             transparency: 0.0,
             texture: Some(load_texture("C:/Users/irvin/UVG/Sexto_Semestre/Graficas/release/textures/door.png")),
             has_texture: true,
+            id: 7,
+            name: "door",
+            emission: 0.0,
+            falls: false,
         }),
         // Log
         Arc::new(Material {
@@ -124,6 +196,10 @@ fn define_materials() -> Vec<Arc<Material>> {
             transparency: 0.8,
             texture: Some(load_texture("C:/Users/irvin/UVG/Sexto_Semestre/Graficas/release/textures/log.png")),
             has_texture: true,
+            id: 8,
+            name: "log",
+            emission: 0.0,
+            falls: false,
         }),
         // Leaves
         Arc::new(Material {
@@ -134,6 +210,10 @@ fn define_materials() -> Vec<Arc<Material>> {
             transparency: 0.2,
             texture: Some(load_texture("C:/Users/irvin/UVG/Sexto_Semestre/Graficas/release/textures/leaves.png")),
             has_texture: true,
+            id: 9,
+            name: "leaves",
+            emission: 0.0,
+            falls: false,
         }),
         // Water
         Arc::new(Material {
@@ -144,20 +224,76 @@ fn define_materials() -> Vec<Arc<Material>> {
             transparency: 5.8,
             texture: Some(load_texture("C:/Users/irvin/UVG/Sexto_Semestre/Graficas/release/textures/water.png")),
             has_texture: true,
+            id: 10,
+            name: "water",
+            emission: 0.0,
+            falls: false,
+        }),
+        // Magma
+        Arc::new(Material {
+            diffuse: Color::new(200, 80, 20),
+            specular: 10.0,
+            albedo: [0.6, 0.2, 0.0, 0.0],
+            refractive_index: 1.0,
+            transparency: 0.0,
+            texture: Some(load_texture("C:/Users/irvin/UVG/Sexto_Semestre/Graficas/release/textures/magma.png")),
+            has_texture: true,
+            id: 11,
+            name: "magma",
+            emission: 0.8, // La lava brilla más que la redstone
+            falls: false,
+        }),
+        // TNT
+        Arc::new(Material {
+            diffuse: Color::new(200, 50, 40),
+            specular: 10.0,
+            albedo: [0.8, 0.2, 0.0, 0.0],
+            refractive_index: 1.0,
+            transparency: 0.0,
+            texture: Some(load_texture("C:/Users/irvin/UVG/Sexto_Semestre/Graficas/release/textures/tnt.png")),
+            has_texture: true,
+            id: 12,
+            name: "tnt",
+            emission: 0.0,
+            falls: false,
+        }),
+        // Sand (sin textura: color liso)
+        Arc::new(Material {
+            diffuse: Color::new(215, 190, 130),
+            specular: 5.0,
+            albedo: [0.2, 0.1, 0.0, 0.0],
+            refractive_index: 1.0,
+            transparency: 0.0,
+            texture: None,
+            has_texture: false,
+            id: 13,
+            name: "sand",
+            emission: 0.0,
+            falls: true,
         }),
     ]
 }
 
+// Lista de objetos para render a partir de cubos
+fn boxed_objects<'a>(cubes: impl Iterator<Item = &'a Cube>) -> Vec<Box<dyn RayIntersect>> {
+    cubes.map(|cube| Box::new(cube.clone()) as Box<dyn RayIntersect>).collect()
+}
+
+// Valor que sigue a una opción de la línea de comandos, p. ej. `--scene diorama.txt`
+fn arg_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .cloned()
+}
 
 fn main() {
     let width = 800;
     let height = 600;
 
-    let mut last_frame_time = Instant::now();
-    let mut fps = 60.0;
-    let fps_threshold = 30.0;
+    let args: Vec<String> = std::env::args().collect();
 
-    let mut framebuffer = Framebuffer::new(width, height);
+    let mut last_frame_time = Instant::now();
 
     // Definir los materiales
     let materials = define_materials();
@@ -172,11 +308,14 @@ fn main() {
     let log_material = materials[8].clone();
     let leaves_material = materials[9].clone();
     let water_material = materials[10].clone();
+    let magma_material = materials[11].clone();
+    let tnt_material = materials[12].clone();
+    let sand_material = materials[13].clone();
 
     let cube_size = 0.5;
 
      //Objetos estatiscos
-    let mut static_objects: Vec<Box<dyn RayIntersect>> = Vec::new();
+    let mut static_objects: Vec<Cube> = Vec::new();
 
     //**MOSS**
     // Borde superior e inferior del rectángulo de moss (sin espacio entre los cubos)
@@ -187,7 +326,7 @@ fn main() {
             cube_size,                                  // Tamaño del cubo
             moss_material.clone(),  // Usar Arc<Material> para optimizar memoria
         );
-        static_objects.push(Box::new(cube_top));
+        static_objects.push(cube_top);
 
         // Parte inferior (fila 6)
         let cube_bottom = Cube::new(
@@ -195,7 +334,7 @@ fn main() {
             cube_size,                                               // Tamaño del cubo
             moss_material.clone(),  // Usar Arc<Material> para optimizar memoria
         );
-        static_objects.push(Box::new(cube_bottom));
+        static_objects.push(cube_bottom);
     }
 
     // Lados del rectángulo de moss (sin espacio entre los cubos)
@@ -206,7 +345,7 @@ fn main() {
             cube_size,                                    // Tamaño del cubo
             moss_material.clone(),  // Usar Arc<Material> para optimizar memoria
         );
-        static_objects.push(Box::new(cube_left));
+        static_objects.push(cube_left);
 
         // Lado derecho
         let cube_right = Cube::new(
@@ -214,7 +353,7 @@ fn main() {
             cube_size,                                                 // Tamaño del cubo
             moss_material.clone(),  // Usar Arc<Material> para optimizar memoria
         );
-        static_objects.push(Box::new(cube_right));
+        static_objects.push(cube_right);
     }
 
     //Añadir moss para la CABEZA y PATAS de la tortuga
@@ -237,7 +376,7 @@ fn main() {
     );
 
     let moss_cube4 = Cube::new(
-        Vec3::new(-1.0 * cube_size, 0.0 * cube_size, -2.0 * cube_size),  // Posición en la casilla superior (5)
+        Vec3::new(-cube_size, 0.0 * cube_size, -2.0 * cube_size),  // Posición en la casilla superior (5)
         cube_size,  // Tamaño del cubo
         moss_material.clone(),  // Usar el material de blackstone
     );
@@ -270,14 +409,14 @@ fn main() {
 
 
     // Añadir el cubo a la lista de objetos
-    static_objects.push(Box::new(moss_cube));
-    static_objects.push(Box::new(moss_cube2));
-    static_objects.push(Box::new(moss_cube3));
-    static_objects.push(Box::new(moss_cube4));
-    static_objects.push(Box::new(moss_cube5));
-    static_objects.push(Box::new(moss_cube6));
-    static_objects.push(Box::new(moss_cube7));
-    static_objects.push(Box::new(moss_cube8));
+    static_objects.push(moss_cube);
+    static_objects.push(moss_cube2);
+    static_objects.push(moss_cube3);
+    static_objects.push(moss_cube4);
+    static_objects.push(moss_cube5);
+    static_objects.push(moss_cube6);
+    static_objects.push(moss_cube7);
+    static_objects.push(moss_cube8);
 
 
     //**DIRT CUBOS BASE DEL FONDO**//
@@ -291,7 +430,7 @@ fn main() {
                     cube_size,                                                                       // Tamaño del cubo
                     dirt_material.clone(),  // Usar Arc<Material> para optimizar memoria
                 );
-                static_objects.push(Box::new(cube_dirt));
+                static_objects.push(cube_dirt);
             }
         }
     }
@@ -323,7 +462,7 @@ fn main() {
     );
     
     let dirt_cube_5 = Cube::new(
-        Vec3::new(cube_size * 4.0, 2.0 * cube_size, -1.0 * cube_size),  // Posición para el segundo cubo de dirt
+        Vec3::new(cube_size * 4.0, 2.0 * cube_size, -cube_size),  // Posición para el segundo cubo de dirt
         cube_size,  // Tamaño del cubo
         dirt_material.clone(),  // Usar el material de dirt
     );
@@ -353,15 +492,15 @@ fn main() {
     );
         
     // Añadir los cubos a la lista de objetos
-    static_objects.push(Box::new(dirt_cube_1));
-    static_objects.push(Box::new(dirt_cube_2));
-    static_objects.push(Box::new(dirt_cube_3));
-    static_objects.push(Box::new(dirt_cube_4));
-    static_objects.push(Box::new(dirt_cube_5));
-    static_objects.push(Box::new(dirt_cube_6));
-    static_objects.push(Box::new(dirt_cube_7));
-    static_objects.push(Box::new(dirt_cube_8));
-    static_objects.push(Box::new(dirt_cube_9));
+    static_objects.push(dirt_cube_1);
+    static_objects.push(dirt_cube_2);
+    static_objects.push(dirt_cube_3);
+    static_objects.push(dirt_cube_4);
+    static_objects.push(dirt_cube_5);
+    static_objects.push(dirt_cube_6);
+    static_objects.push(dirt_cube_7);
+    static_objects.push(dirt_cube_8);
+    static_objects.push(dirt_cube_9);
 
     // Añadir una fila de cubos de dirt desde la primera hasta la última posición
     for x in 0..=4 {
@@ -371,7 +510,7 @@ fn main() {
             dirt_material.clone(),  // Usar el material de dirt
         );
         // Añadir el cubo a la lista de objetos
-        static_objects.push(Box::new(dirt_cube));
+        static_objects.push(dirt_cube);
     }
 
     //STONE FILA DE LA IZQUIERDA
@@ -381,7 +520,7 @@ fn main() {
             cube_size,  // Tamaño del cubo
             stone_material.clone(),  // Usar Arc<Material> para optimizar memoria
         );
-        static_objects.push(Box::new(cube_stone));
+        static_objects.push(cube_stone);
     }
 
     // Añadir un cubo individual de stone en la posición correcta
@@ -407,16 +546,12 @@ fn main() {
     );
 
     // Añadir el cubo a la lista de objetos
-    static_objects.push(Box::new(single_stone_cube));
-    static_objects.push(Box::new(single_stone_cube2));
-    static_objects.push(Box::new(single_stone_cube3));
+    static_objects.push(single_stone_cube);
+    static_objects.push(single_stone_cube2);
+    static_objects.push(single_stone_cube3);
 
     //**REDSTONE**//
     // Añadir un cubo de redstone en una posición que tú elijas
-    let redstone_material = materials[3].clone();  // Usar el material de redstone
-
-    let redstone_material2 = materials[3].clone();  // Usar el material de redstone
-
     let redstone_cube = Cube::new(
         Vec3::new(-cube_size, 3.0 * cube_size, -3.0 * cube_size),  // Posición para el segundo cubo de dirt
         cube_size,  // Tamaño del cubo
@@ -437,9 +572,9 @@ fn main() {
     );
 
     // Añadir el cubo a la lista de objetos
-    static_objects.push(Box::new(redstone_cube));
-    static_objects.push(Box::new(redstone_cube2));
-    static_objects.push(Box::new(redstone_cube3));
+    static_objects.push(redstone_cube);
+    static_objects.push(redstone_cube2);
+    static_objects.push(redstone_cube3);
 
     //VIOLET STONE//
     // Crear un cubo de violetstone en una posición específica
@@ -457,14 +592,14 @@ fn main() {
     );
 
     // Añadir el cubo a la lista de objetos
-    static_objects.push(Box::new(violetstone_cube));
-    static_objects.push(Box::new(violetstone_cube2));
+    static_objects.push(violetstone_cube);
+    static_objects.push(violetstone_cube2);
 
     //**BLACKSTONE**
 
     // Crear un cubo de blackstone en una posición específica
     let blackstone_cube = Cube::new(
-        Vec3::new(cube_size * 4.0, 1.0 * cube_size, -1.0 * cube_size),  // Posición para el segundo cubo de dirt
+        Vec3::new(cube_size * 4.0, 1.0 * cube_size, -cube_size),  // Posición para el segundo cubo de dirt
         cube_size,  // Tamaño del cubo
         blackstone_material.clone(),  // Usar el material de blackstone
     );
@@ -482,7 +617,7 @@ fn main() {
     );
 
     let blackstone_cube4 = Cube::new(
-        Vec3::new(cube_size * 1.0, 1.0 * cube_size, -1.0 * cube_size),  // Posición para el segundo cubo de dirt
+        Vec3::new(cube_size * 1.0, 1.0 * cube_size, -cube_size),  // Posición para el segundo cubo de dirt
         cube_size,  // Tamaño del cubo
         blackstone_material.clone(),  // Usar el material de blackstone
     );
@@ -494,11 +629,11 @@ fn main() {
     );
 
     // Añadir el cubo a la lista de objetos
-    static_objects.push(Box::new(blackstone_cube));
-    static_objects.push(Box::new(blackstone_cube2));
-    static_objects.push(Box::new(blackstone_cube3));
-    static_objects.push(Box::new(blackstone_cube4));
-    static_objects.push(Box::new(blackstone_cube5));
+    static_objects.push(blackstone_cube);
+    static_objects.push(blackstone_cube2);
+    static_objects.push(blackstone_cube3);
+    static_objects.push(blackstone_cube4);
+    static_objects.push(blackstone_cube5);
 
 
     //**WOOD**//
@@ -510,7 +645,7 @@ fn main() {
             cube_size,  // Tamaño del cubo
             wood_material.clone(),  // Usar el material de wood
         );
-        static_objects.push(Box::new(cube_wood));
+        static_objects.push(cube_wood);
     }
 
     for y in 4..=6 { // Desde la posición 4 hasta la 6 en el eje Y
@@ -519,7 +654,7 @@ fn main() {
             cube_size,  // Tamaño del cubo
             wood_material.clone(),  // Usar el material de wood
         );
-        static_objects.push(Box::new(cube_wood));
+        static_objects.push(cube_wood);
     }
 
     let wood_cube = Cube::new(
@@ -548,10 +683,10 @@ fn main() {
 
 
     // Añadir el cubo a la lista de objetos
-    static_objects.push(Box::new(wood_cube));
-    static_objects.push(Box::new(wood_cube2));
-    static_objects.push(Box::new(wood_cube3));
-    static_objects.push(Box::new(wood_cube4));
+    static_objects.push(wood_cube);
+    static_objects.push(wood_cube2);
+    static_objects.push(wood_cube3);
+    static_objects.push(wood_cube4);
 
     for y in 4..=5 { // Desde la posición 4 hasta la 6 en el eje Y
         let cube_wood = Cube::new(
@@ -559,7 +694,7 @@ fn main() {
             cube_size,  // Tamaño del cubo
             wood_material.clone(),  // Usar el material de wood
         );
-        static_objects.push(Box::new(cube_wood));
+        static_objects.push(cube_wood);
     }
 
     //*DOOR*//
@@ -578,8 +713,8 @@ fn main() {
     );
 
     // Añadir los cubos a la lista de objetos
-    static_objects.push(Box::new(door));
-    static_objects.push(Box::new(door_upper));
+    static_objects.push(door);
+    static_objects.push(door_upper);
 
     //**LOG PARA EL TRONCO//
     for y in 4..=6 { // Desde la posición 4 hasta la 6 en el eje Y
@@ -588,7 +723,7 @@ fn main() {
             cube_size,  // Tamaño del cubo
             log_material.clone(),  // Usar el material de wood
         );
-        static_objects.push(Box::new(cube_log));
+        static_objects.push(cube_log);
     }
 
     //**LEAVES PARA LAS HOJAS DEL TRONCO**//
@@ -598,7 +733,7 @@ fn main() {
             cube_size,  // Tamaño del cubo
             leaves_material.clone(),  // Usar el material de wood
         );
-        static_objects.push(Box::new(leaves_cube));
+        static_objects.push(leaves_cube);
     }
 
     for z in -5..=-2 { // Desde la posición 4 hasta la 6 en el eje Y
@@ -607,7 +742,7 @@ fn main() {
             cube_size,  // Tamaño del cubo
             leaves_material.clone(),  // Usar el material de wood
         );
-        static_objects.push(Box::new(leaves_cube));
+        static_objects.push(leaves_cube);
     }
 
     for z in -5..=-2 { // Desde la posición 4 hasta la 6 en el eje Y
@@ -616,7 +751,7 @@ fn main() {
             cube_size,  // Tamaño del cubo
             leaves_material.clone(),  // Usar el material de wood
         );
-        static_objects.push(Box::new(leaves_cube));
+        static_objects.push(leaves_cube);
     }
 
     for z in -5..=-2 { // Desde la posición 4 hasta la 6 en el eje Y
@@ -625,7 +760,7 @@ fn main() {
             cube_size,  // Tamaño del cubo
             leaves_material.clone(),  // Usar el material de wood
         );
-        static_objects.push(Box::new(leaves_cube));
+        static_objects.push(leaves_cube);
     }
 
     for z in -5..=-2 { // Desde la posición 4 hasta la 6 en el eje Y
@@ -634,7 +769,7 @@ fn main() {
             cube_size,  // Tamaño del cubo
             leaves_material.clone(),  // Usar el material de wood
         );
-        static_objects.push(Box::new(leaves_cube));
+        static_objects.push(leaves_cube);
     }
 
     for z in -5..=-2 { // Desde la posición 4 hasta la 6 en el eje Y
@@ -643,7 +778,7 @@ fn main() {
             cube_size,  // Tamaño del cubo
             leaves_material.clone(),  // Usar el material de wood
         );
-        static_objects.push(Box::new(leaves_cube));
+        static_objects.push(leaves_cube);
     }
 
     
@@ -668,26 +803,43 @@ fn main() {
 
 
     // Añadir los cubos a la lista de objetos
-    static_objects.push(Box::new(leaves_cube));
-    static_objects.push(Box::new(leaves_cube2));
-    static_objects.push(Box::new(leaves_cube3));
-
+    static_objects.push(leaves_cube);
+    static_objects.push(leaves_cube2);
+    static_objects.push(leaves_cube3);
+
+    //**MAGMA**
+    // Dos bloques de lava en las esquinas del borde de blackstone; de ellos salen chispas y humo
+    for x in [1.0, 4.0] {
+        static_objects.push(Cube::new(
+            Vec3::new(x * cube_size, 1.0 * cube_size, 0.0),
+            cube_size,
+            magma_material.clone(),
+        ));
+    }
 
+    //**TNT Y ARENA**
+    // Una TNT junto a una columna de arena: al explotar se lleva la base y la arena de arriba cae
+    static_objects.push(Cube::new(Vec3::new(0.0, cube_size, -cube_size), cube_size, tnt_material.clone()));
+    for y in 1..=4 {
+        static_objects.push(Cube::new(
+            Vec3::new(0.0, y as f32 * cube_size, -2.0 * cube_size),
+            cube_size,
+            sand_material.clone(),
+        ));
+    }
 
 
     //--------------------------------------------------------------------------------------------------------------------------//
 
-    //Objetos con animacion
-    let mut water_cubes: Vec<Cube> = vec![
+    // Fuentes de agua: la simulación la hace correr desde aquí por la maqueta
+    let mut water_sources: Vec<Cube> = vec![
         Cube::new(Vec3::new(3.0 * cube_size, 2.0 * cube_size, -2.0 * cube_size), cube_size, water_material.clone()),
         Cube::new(Vec3::new(2.0 * cube_size, 2.0 * cube_size, -2.0 * cube_size), cube_size, water_material.clone()),
-        Cube::new(Vec3::new(3.0 * cube_size, 1.5 * cube_size, -1.0 * cube_size), cube_size, water_material.clone()),
-        Cube::new(Vec3::new(2.0 * cube_size, 1.5 * cube_size, -1.0 * cube_size), cube_size, water_material.clone()),
     ];
 
 
 
-    let light = Light::new(
+    let mut light = Light::new(
         Vec3::new(0.0, 12.0, 20.0),
         Color::new(116, 140, 153),
         3.0,
@@ -697,12 +849,308 @@ fn main() {
         Vec3::new(0.0, 2.5, 5.0),
         Vec3::new(0.0, 0.0, 0.0),
         Vec3::new(0.0, 1.0, 0.0),
-        45.0,
+        60.0,
         width as f32 / height as f32,
-        50.1,
-        95.0,
+        0.1,
+        100.0,
     );
 
+    // Ajustes de cámara opcionales desde un archivo de escena; si trae bloques, reemplazan la maqueta
+    let scene_path = arg_value(&args, "--scene");
+    let mut animation = Animation::default();
+    if let Some(scene_path) = &scene_path {
+        match scene::load_scene(scene_path) {
+            Ok(scene) => {
+                scene.camera.apply(&mut camera);
+                animation.tracks = scene.tracks.clone();
+                if !scene.blocks.is_empty() {
+                    static_objects.clear();
+                    water_sources.clear();
+                }
+                // Los `@N` de las pistas cuentan los bloques del archivo; los que no quedan en
+                // static_objects (agua y materiales desconocidos) se descuentan de atrás hacia adelante
+                let mut skipped = Vec::new();
+                for (index, block) in scene.blocks.iter().enumerate() {
+                    match materials.iter().find(|material| material.name == block.material) {
+                        // Los bloques de agua son fuentes de la simulación
+                        Some(material) if Arc::ptr_eq(material, &water_material) => {
+                            water_sources.push(Cube::new(block.position, cube_size, material.clone()));
+                            skipped.push(index);
+                        }
+                        Some(material) => static_objects.push(Cube::new(block.position, cube_size, material.clone())),
+                        None => {
+                            eprintln!("Material desconocido '{}'", block.material);
+                            skipped.push(index);
+                        }
+                    }
+                }
+                for index in skipped.into_iter().rev() {
+                    animation.block_removed(index);
+                }
+            }
+            Err(e) => eprintln!("Error al cargar la escena: {}", e),
+        }
+    }
+    if let Some(projection) = arg_value(&args, "--projection") {
+        match scene::parse_projection(&[projection.as_str()]) {
+            Ok(projection) => camera.projection = projection,
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    if let Some(stereo) = arg_value(&args, "--stereo") {
+        match scene::parse_stereo(&[stereo.as_str()]) {
+            Ok(stereo) => camera.stereo = stereo,
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    // `--debug-view normals|uv|distance|material|shadow|heatmap` reemplaza el sombreado
+    let mut render_settings = RenderSettings::default();
+    if let Some(name) = arg_value(&args, "--debug-view") {
+        match DebugView::from_name(&name) {
+            Some(view) => render_settings.debug_view = view,
+            None => eprintln!("Vista de depuración desconocida '{}'", name),
+        }
+    }
+
+    // Posprocesado: `--post bloom,vignette,...` activa efectos, `--lut` carga una tabla .cube
+    let mut post = PostProcess::new();
+    if let Some(list) = arg_value(&args, "--post") {
+        if let Err(e) = post.enable_list(&list) {
+            eprintln!("{}", e);
+        }
+    }
+    // `--exposure` fija una exposición manual: la automática se apaga salvo con `--auto-exposure`
+    if let Some(ev) = arg_value(&args, "--exposure").and_then(|v| v.parse().ok()) {
+        post.exposure_ev = ev;
+        post.set_enabled(Effect::Exposure, true);
+        post.set_auto_exposure(false);
+    }
+    if args.iter().any(|arg| arg == "--auto-exposure") {
+        post.set_auto_exposure(true);
+    }
+    if args.iter().any(|arg| arg == "--no-auto-exposure") {
+        post.set_auto_exposure(false);
+    }
+    if let Some(path) = arg_value(&args, "--lut") {
+        match Lut::load(&path) {
+            Ok(lut) => {
+                post.lut = Some(lut);
+                post.set_enabled(Effect::Lut, true);
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    // Ciclo de día: `--time-of-day <hora>` fija la hora (sol, luna y cielo) y `--day-length <segundos>`
+    // hace correr el día; sin ninguno se mantiene la luz fija de siempre
+    let time_of_day: Option<f32> = arg_value(&args, "--time-of-day").and_then(|v| v.parse().ok());
+    let day_length: Option<f32> = arg_value(&args, "--day-length").and_then(|v| v.parse().ok());
+    let mut day_cycle = DayCycle::new(time_of_day.unwrap_or(10.0), day_length.unwrap_or(120.0));
+    day_cycle.enabled = time_of_day.is_some() || day_length.is_some();
+    let (scene_min, scene_max) = scene_bounds(&boxed_objects(static_objects.iter().chain(water_sources.iter())), cube_size);
+    let scene_center = (scene_min + scene_max) / 2.0;
+
+    // Salida sin ventana: `--output` y/o `--animation` renderizan a archivos y terminan
+    let output_path = arg_value(&args, "--output");
+    let animation_path = arg_value(&args, "--animation");
+    if output_path.is_some() || animation_path.is_some() {
+        let out_width = arg_value(&args, "--width").and_then(|v| v.parse().ok()).unwrap_or(width);
+        // Los panoramas equirectangulares cubren 360° x 180°, así que por defecto son 2:1
+        let default_height = if camera.projection == Projection::Equirectangular {
+            out_width / 2
+        } else {
+            out_width * height / width
+        };
+        let out_height = arg_value(&args, "--height").and_then(|v| v.parse().ok()).unwrap_or(default_height);
+        camera.aspect_ratio = out_width as f32 / out_height as f32;
+        let frame_rate = arg_value(&args, "--fps").and_then(|v| v.parse().ok()).unwrap_or(30.0);
+        // `--aux <prefijo>` exporta profundidad, normales, albedo, UV e IDs junto a cada imagen
+        let aux_prefix = arg_value(&args, "--aux");
+        let aux_format = match arg_value(&args, "--aux-format").as_deref() {
+            None | Some("pfm") => AuxFormat::Pfm,
+            Some("png") => AuxFormat::Png,
+            Some(other) => {
+                eprintln!("Formato auxiliar desconocido '{}' (pfm o png)", other);
+                std::process::exit(1);
+            }
+        };
+
+        // `--samples N` acumula N pasadas por cuadro
+        let samples = arg_value(&args, "--samples").and_then(|v| v.parse().ok()).unwrap_or(1);
+        // `--denoise <fuerza>` filtra cada cuadro guiándose por los buffers auxiliares
+        let denoiser = arg_value(&args, "--denoise").and_then(|v| v.parse().ok()).map(Denoiser::new);
+        // `--select 3,7` resalta esos bloques (índices de bloque, como en el inspector)
+        let mut selection = Selection::new();
+        if let Some(list) = arg_value(&args, "--select") {
+            for id in list.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+                match id.parse() {
+                    Ok(id) => selection.ids.push(id),
+                    Err(_) => eprintln!("Bloque inválido '{}' en --select", id),
+                }
+            }
+        }
+
+        // Luz en el instante `time`: con el ciclo de día, el sol y el cielo de esa hora. El día solo
+        // corre durante una secuencia si se pidió `--day-length`.
+        let light_at = |time: f32| -> Light {
+            let mut frame_light = light.clone();
+            if day_cycle.enabled {
+                let mut day = day_cycle.clone();
+                if day_length.is_some() {
+                    day.advance(time);
+                }
+                day.apply(&mut frame_light, scene_center);
+            }
+            frame_light
+        };
+        // Escena y luz en el instante `time` vista desde `eye`: el agua, la física de bloques y las
+        // partículas avanzan en pasos fijos hasta ese instante (así que los tiempos deben ir en
+        // aumento) y la animación se evalúa sobre la pose de reposo. `--no-particles` apaga chispas,
+        // humo, hojas y escombros; `--ignite 12,40` enciende esas TNT al empezar.
+        let mut blocks = static_objects.clone();
+        let mut water = WaterSim::new(&blocks, &water_sources, cube_size);
+        // Las imágenes fijas y las vueltas se renderizan en t=0: el agua ya corrió hasta quedarse quieta
+        let still_scene = arg_value(&args, "--camera-path").is_none()
+            && (arg_value(&args, "--turntable").is_some() || arg_value(&args, "--frames").is_none());
+        if still_scene {
+            water.settle();
+        }
+        let mut physics = BlockPhysics::new(&blocks, cube_size);
+        let mut particles = ParticleSystem::for_blocks(&blocks, cube_size);
+        particles.enabled = !args.iter().any(|arg| arg == "--no-particles");
+        if let Some(list) = arg_value(&args, "--ignite") {
+            for id in list.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+                match id.parse::<usize>().ok().and_then(|id| blocks.get(id)) {
+                    Some(block) if physics.ignite(block) => {}
+                    _ => eprintln!("El bloque '{}' de --ignite no es una TNT", id),
+                }
+            }
+        }
+        let mut sim_steps = 0;
+        // Las pistas siguen a sus bloques aunque la física quite otros
+        let mut animation = animation.clone();
+        let mut scene_at = |time: f32, eye: &Vec3| -> (Vec<Box<dyn RayIntersect>>, Vec<usize>, Light) {
+            while (sim_steps as f32) < (time * STEPS_PER_SECOND).round() {
+                water.step();
+                let result = physics.step(&mut blocks, 1.0 / STEPS_PER_SECOND);
+                for &index in &result.removed {
+                    animation.block_removed(index);
+                }
+                if result.changed {
+                    water.set_solids(&blocks);
+                    particles.set_blocks(&blocks, cube_size);
+                }
+                for center in result.explosions {
+                    particles.explode(center);
+                }
+                particles.step(1.0 / STEPS_PER_SECOND);
+                sim_steps += 1;
+            }
+            let mut rest_blocks: Vec<Cube> = blocks.iter().cloned().chain(water.cubes(&water_material)).collect();
+            physics.dress(&mut rest_blocks);
+            let mut objects = boxed_objects(animation.pose(&rest_blocks, time).iter());
+            // Sin recorte por frustum, el índice de cada bloque en `objects` ya es su identificador
+            let object_ids: Vec<usize> = (0..objects.len()).collect();
+            objects.extend(particles.objects(eye));
+            let mut frame_light = light_at(time);
+            physics.apply_flash(&mut frame_light);
+            (objects, object_ids, frame_light)
+        };
+        let mut render_frame = |objects: &[Box<dyn RayIntersect>], object_ids: &[usize], camera: &Camera, light: &Light| -> Framebuffer {
+            let mut framebuffer = Framebuffer::new(out_width, out_height);
+            if aux_prefix.is_some() || denoiser.is_some() || !selection.is_empty() {
+                framebuffer.enable_aux();
+            }
+            // Varias pasadas con desplazamiento subpixel promediadas (antialiasing y lente más limpio)
+            let mut accumulator = Accumulator::new(samples);
+            accumulator.begin(out_width, out_height, 0);
+            let mut jittered = render_settings;
+            while !accumulator.is_converged() {
+                jittered.jitter = accumulator.jitter();
+                render(&mut framebuffer, objects, object_ids, camera, light, &jittered);
+                accumulator.add(&mut framebuffer);
+            }
+            // En estéreo los buffers auxiliares quedan vacíos y no sirven de guía
+            if let Some(denoiser) = denoiser.as_ref().filter(|_| camera.stereo == StereoMode::Mono) {
+                denoiser.apply(&mut framebuffer);
+            }
+            post.apply(&mut framebuffer, 1.0 / frame_rate);
+            if let Some(aux) = framebuffer.aux.take() {
+                selection.draw_outline(&mut framebuffer, &aux);
+                framebuffer.aux = Some(aux);
+            }
+            framebuffer
+        };
+        let exit_on_error = |result: Result<(), String>| {
+            if let Err(e) = result {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        };
+        let open_sink = |frame_count: usize| -> FrameSink {
+            let sink = FrameSink::new(output_path.clone(), animation_path.clone(), out_width, out_height, frame_count, frame_rate)
+                .unwrap_or_else(|e| {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                });
+            match &aux_prefix {
+                Some(prefix) => sink.with_aux(prefix.clone(), aux_format),
+                None => sink,
+            }
+        };
+
+        let (objects, object_ids, start_light) = scene_at(0.0, &camera.eye);
+
+        if let Some(path_file) = arg_value(&args, "--camera-path") {
+            // Con `--camera-path` se renderiza el recorrido completo como secuencia numerada
+            let camera_path = CameraPath::load(&path_file).unwrap_or_else(|e| {
+                eprintln!("Error al cargar el recorrido: {}", e);
+                std::process::exit(1);
+            });
+            let mut sink = open_sink(camera_path.frame_count(frame_rate));
+            for frame in 0..camera_path.frame_count(frame_rate) {
+                let time = frame as f32 / frame_rate;
+                camera_path.apply(time, &mut camera);
+                let (objects, object_ids, frame_light) = scene_at(time, &camera.eye);
+                exit_on_error(sink.push(frame, &render_frame(&objects, &object_ids, &camera, &frame_light)));
+            }
+            exit_on_error(sink.finish());
+        } else if let Some(frames) = arg_value(&args, "--turntable").and_then(|v| v.parse().ok()) {
+            // Vuelta de 360° alrededor del centro de la escena
+            let elevation = arg_value(&args, "--elevation").and_then(|v| v.parse().ok()).unwrap_or(30.0);
+            let turntable = Turntable::new(frames, elevation, args.iter().any(|arg| arg == "--rotate-light"));
+            turntable.setup(&mut camera, scene_center);
+
+            let mut sink = open_sink(turntable.frames);
+            for frame in 0..turntable.frames {
+                let frame_light = turntable.light_for_frame(&start_light, scene_center, frame);
+                exit_on_error(sink.push(frame, &render_frame(&objects, &object_ids, &camera, &frame_light)));
+                turntable.step(&mut camera);
+            }
+            exit_on_error(sink.finish());
+        } else if let Some(frames) = arg_value(&args, "--frames").and_then(|v| v.parse().ok()) {
+            // La animación de la escena con la cámara fija
+            let mut sink = open_sink(frames);
+            for frame in 0..frames {
+                let time = frame as f32 / frame_rate;
+                let (objects, object_ids, frame_light) = scene_at(time, &camera.eye);
+                exit_on_error(sink.push(frame, &render_frame(&objects, &object_ids, &camera, &frame_light)));
+            }
+            exit_on_error(sink.finish());
+        } else if let Some(path) = &output_path {
+            let framebuffer = render_frame(&objects, &object_ids, &camera, &start_light);
+            exit_on_error(framebuffer.save(path));
+            if let Some(prefix) = &aux_prefix {
+                exit_on_error(save_aux_buffers(&framebuffer, prefix, aux_format));
+            }
+        } else {
+            eprintln!("--animation necesita --frames, --turntable o --camera-path");
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let mut window = Window::new(
         "Irving's Diorama",
         width,
@@ -712,75 +1160,628 @@ fn main() {
         panic!("{}", e);
     });
 
-    let camera_speed = 0.1;
-    let camera_rotate_speed = 0.05;
+    // Velocidades por segundo de tiempo real: no dependen de los cuadros por segundo ni de la
+    // escala de tiempo de la simulación
+    let camera_speed = 3.0;        // Unidades por segundo en órbita
+    let camera_rotate_speed = 1.5; // Radianes por segundo con las flechas
+    let aperture_speed = 0.15;     // Radio de apertura por segundo
+    let max_aperture = 0.3;
+    let zoom_speed = 30.0;         // Grados de campo de visión por segundo
+    let fly_speed = 2.5;          // Unidades por segundo en vuelo libre
+    let fly_boost = 3.0;          // Multiplicador con Shift
+    let mouse_sensitivity = 0.005; // Radianes por pixel de movimiento del ratón
+    let click_tolerance = 4.0; // Pixeles que se puede mover el ratón en un clic derecho sin que sea arrastre
+    let mut last_mouse_pos: Option<(f32, f32)> = None;
+
+    // Recorrido de cámara: K graba keyframes, J reproduce, L guarda, Retroceso borra
+    let camera_path_file = arg_value(&args, "--camera-path").unwrap_or_else(|| "camera_path.txt".to_string());
+    let mut camera_path = CameraPath::load(&camera_path_file).unwrap_or_else(|_| CameraPath::new(Interpolation::CatmullRom));
+    let mut last_keyframe_instant: Option<Instant> = None;
+    let loaded_key_gap = 1.0; // Segundos entre el último keyframe cargado y el primero grabado después
+    let mut playback_start: Option<Instant> = None;
+
+    // T hace girar la cámara alrededor del centro de la escena, como en la vuelta de 360°
+    let turntable_speed = 0.5; // Radianes por segundo
+    let mut turntable_spin = false;
+
+    // N activa el filtro de ruido; , y . cambian su fuerza
+    let mut denoiser = Denoiser::new(0.5);
+    denoiser.enabled = false;
+    let denoise_step = 0.05;
+
+    // B alterna la acumulación progresiva
+    let mut accumulator = Accumulator::new(64);
+    // Reloj de la simulación a 60 pasos por segundo; Z pausa (la animación si no reinicia la
+    // acumulación), X avanza un paso, RePág y AvPág duplican o reducen a la mitad la escala de tiempo
+    let mut clock = SimulationClock::new(STEPS_PER_SECOND);
+    // El agua corre sobre su grid con los pasos del reloj
+    let mut water = WaterSim::new(&static_objects, &water_sources, cube_size);
+    // R apaga o enciende las partículas (chispas y humo de la lava, hojas del árbol)
+    let mut particles = ParticleSystem::for_blocks(&static_objects, cube_size);
+    // La arena cae y la TNT explota con los pasos del reloj; Q enciende todas las TNT y el clic
+    // del medio la que está bajo el ratón
+    let mut physics = BlockPhysics::new(&static_objects, cube_size);
+    // M activa el ciclo de día, que corre con el reloj de la simulación; Inicio y Fin mueven la hora
+    let base_light = light.clone();
+    let mut framebuffer = Framebuffer::new(width, height);
 
-    let mut t = 0.0;
+    // Escala de render adaptativa hacia `--target-fps` (30 por defecto); `--upscale nearest|bilinear`
+    let target_fps = arg_value(&args, "--target-fps").and_then(|v| v.parse().ok()).unwrap_or(30.0);
+    let mut resolution = ResolutionController::new(target_fps);
+    if arg_value(&args, "--upscale").as_deref() == Some("nearest") {
+        resolution.filter = UpscaleFilter::Nearest;
+    }
+    let mut window_buffer = Framebuffer::new(width, height);
+    let mut hud = Hud::new(resolution.target_frame_time);
+    let mut inspected: Option<Pick> = None;
+    let mut selection = Selection::new();
+    let mut left_was_down = false;
+    let mut right_was_down = false;
+    // Dónde se apretó el botón derecho y si ya se arrastró (en vuelo libre, arrastrar es mirar)
+    let mut right_press_pos: Option<(f32, f32)> = None;
+    let mut right_dragged = false;
+    let mut middle_was_down = false;
+
+    // Barra del editor: todos los materiales menos el agua, que se anima aparte
+    let mut editor = Editor::new(materials.iter().filter(|m| !Arc::ptr_eq(m, &water_material)).cloned().collect());
 
     while window.is_open() && !window.is_key_down(Key::Escape) {
         let current_time = Instant::now();
         let delta_time = current_time.duration_since(last_frame_time);
         last_frame_time = current_time;
+        // Segundos reales del cuadro, para mover la cámara
+        let frame_seconds = delta_time.as_secs_f32();
 
-        fps = 1.0 / delta_time.as_secs_f32();
-        let es_mucha_carga = fps < fps_threshold;
-        let scale_factor = if es_mucha_carga { 0.5 } else { 1.0 };
-        let scaled_width = (width as f32 * scale_factor) as usize;
-        let scaled_height = (height as f32 * scale_factor) as usize;
+        let frustum = Frustum::new(&camera);
 
-        framebuffer = Framebuffer::new(scaled_width, scaled_height);
+        // C alterna entre órbita y vuelo libre
+        if window.is_key_pressed(Key::C, KeyRepeat::No) {
+            camera.toggle_mode();
+        }
 
-        let frustum = Frustum::new(&camera);
+        // Editando, Ctrl es para los atajos (Ctrl+Z, Ctrl+Y, Ctrl+S): mientras está apretado las
+        // teclas no mueven la cámara, y en vuelo libre tampoco baja
+        let ctrl_down = window.is_key_down(Key::LeftCtrl) || window.is_key_down(Key::RightCtrl);
+        let shortcut_held = editor.enabled && ctrl_down;
+
+        match camera.mode {
+            _ if shortcut_held => {}
+            CameraMode::Orbit => {
+                let camera_speed = camera_speed * frame_seconds;
+                let camera_rotate_speed = camera_rotate_speed * frame_seconds;
+                if window.is_key_down(Key::W) {
+                    camera.move_camera(vec3(0.0, 0.0, -camera_speed));
+                }
+                if window.is_key_down(Key::S) {
+                    camera.move_camera(vec3(0.0, 0.0, camera_speed));
+                }
+                if window.is_key_down(Key::A) {
+                    camera.move_camera(vec3(-camera_speed, 0.0, 0.0));
+                }
+                if window.is_key_down(Key::D) {
+                    camera.move_camera(vec3(camera_speed, 0.0, 0.0));
+                }
+
+                if window.is_key_down(Key::Up) {
+                    camera.orbit(0.0, -camera_rotate_speed);
+                }
+                if window.is_key_down(Key::Down) {
+                    camera.orbit(0.0, camera_rotate_speed);
+                }
+                if window.is_key_down(Key::Left) {
+                    camera.orbit(-camera_rotate_speed, 0.0);
+                }
+                if window.is_key_down(Key::Right) {
+                    camera.orbit(camera_rotate_speed, 0.0);
+                }
+            }
+            CameraMode::Fly => {
+                // Velocidad por segundo, escalada por el tiempo del cuadro
+                let boost = if window.is_key_down(Key::LeftShift) { fly_boost } else { 1.0 };
+                let step = fly_speed * boost * frame_seconds;
+                let camera_rotate_speed = camera_rotate_speed * frame_seconds;
+
+                let mut forward = 0.0;
+                let mut right = 0.0;
+                let mut up = 0.0;
+                if window.is_key_down(Key::W) { forward += step; }
+                if window.is_key_down(Key::S) { forward -= step; }
+                if window.is_key_down(Key::D) { right += step; }
+                if window.is_key_down(Key::A) { right -= step; }
+                if window.is_key_down(Key::Space) { up += step; }
+                if window.is_key_down(Key::LeftCtrl) { up -= step; }
+                camera.fly(forward, right, up);
+
+                // Las flechas también giran la mirada
+                if window.is_key_down(Key::Up) {
+                    camera.look(0.0, camera_rotate_speed);
+                }
+                if window.is_key_down(Key::Down) {
+                    camera.look(0.0, -camera_rotate_speed);
+                }
+                if window.is_key_down(Key::Left) {
+                    camera.look(-camera_rotate_speed, 0.0);
+                }
+                if window.is_key_down(Key::Right) {
+                    camera.look(camera_rotate_speed, 0.0);
+                }
+            }
+        }
 
-        if window.is_key_down(Key::W) {
-            camera.move_camera(vec3(0.0, 0.0, -camera_speed));
+        // Mirar con el ratón: arrastrar con el botón derecho en vuelo libre
+        let mouse_pos = window.get_mouse_pos(MouseMode::Pass);
+        if camera.mode == CameraMode::Fly && window.get_mouse_down(MouseButton::Right) {
+            if let (Some((x, y)), Some((last_x, last_y))) = (mouse_pos, last_mouse_pos) {
+                camera.look((x - last_x) * mouse_sensitivity, -(y - last_y) * mouse_sensitivity);
+            }
         }
-        if window.is_key_down(Key::S) {
-            camera.move_camera(vec3(0.0, 0.0, camera_speed));
+        last_mouse_pos = mouse_pos;
+        if window.get_mouse_down(MouseButton::Right) {
+            match (right_press_pos, mouse_pos) {
+                (None, position) => {
+                    right_press_pos = position;
+                    right_dragged = false;
+                }
+                (Some((press_x, press_y)), Some((x, y))) => {
+                    right_dragged |= (x - press_x).abs() + (y - press_y).abs() > click_tolerance;
+                }
+                _ => {}
+            }
+        } else {
+            right_press_pos = None;
+        }
+
+        let mut objects: Vec<Box<dyn RayIntersect>> = Vec::new();
+        // Identificador estable de cada objeto renderizado: su índice en static_objects,
+        // o static_objects.len() + i para la celda de agua i
+        let mut object_ids: Vec<usize> = Vec::new();
+
+        if window.is_key_pressed(Key::Z, KeyRepeat::No) && !ctrl_down {
+            clock.toggle_pause();
         }
-        if window.is_key_down(Key::A) {
-            camera.move_camera(vec3(-camera_speed, 0.0, 0.0));
+        if window.is_key_pressed(Key::X, KeyRepeat::Yes) {
+            clock.single_step();
         }
-        if window.is_key_down(Key::D) {
-            camera.move_camera(vec3(camera_speed, 0.0, 0.0));
+        if window.is_key_pressed(Key::PageUp, KeyRepeat::No) {
+            clock.scale_by(2.0);
         }
+        if window.is_key_pressed(Key::PageDown, KeyRepeat::No) {
+            clock.scale_by(0.5);
+        }
+        // Pasos fijos de este cuadro; la animación se evalúa en el tiempo simulado resultante
+        let steps = clock.advance(frame_seconds);
+        let t = clock.time;
 
-        if window.is_key_down(Key::Up) {
-            camera.orbit(0.0, -camera_rotate_speed);
+        if window.is_key_pressed(Key::M, KeyRepeat::No) {
+            day_cycle.enabled = !day_cycle.enabled;
         }
-        if window.is_key_down(Key::Down) {
-            camera.orbit(0.0, camera_rotate_speed);
+        if window.is_key_pressed(Key::Home, KeyRepeat::Yes) {
+            day_cycle.shift_hours(-1.0);
         }
-        if window.is_key_down(Key::Left) {
-            camera.orbit(-camera_rotate_speed, 0.0);
+        if window.is_key_pressed(Key::End, KeyRepeat::Yes) {
+            day_cycle.shift_hours(1.0);
         }
-        if window.is_key_down(Key::Right) {
-            camera.orbit(camera_rotate_speed, 0.0);
+        if day_cycle.enabled {
+            day_cycle.advance(steps as f32 * clock.step);
+            day_cycle.apply(&mut light, scene_center);
+        } else {
+            light = base_light.clone();
         }
 
-        let mut objects: Vec<Box<dyn RayIntersect>> = Vec::new();
+        if window.is_key_pressed(Key::Q, KeyRepeat::No) {
+            for block in &static_objects {
+                physics.ignite(block);
+            }
+        }
 
-        // Filtrar objetos dentro del frustum
-        for obj in static_objects.iter() {
-            let obj_center = obj.position();
-            if frustum.is_sphere_in_frustum(obj_center, cube_size / 2.0) {
-                objects.push(obj.as_ref().clone_box());
+        if window.is_key_pressed(Key::R, KeyRepeat::No) {
+            particles.toggle();
+        }
+        for _ in 0..steps {
+            water.step();
+            // Los bloques que caen o vuelan cambian los índices, así que la selección ya no vale;
+            // las pistas de animación se corren para seguir a sus bloques
+            let result = physics.step(&mut static_objects, clock.step);
+            for &index in &result.removed {
+                animation.block_removed(index);
+            }
+            if result.changed {
+                selection.clear();
+                inspected = None;
+                editor.clear_history();
+                water.set_solids(&static_objects);
+                particles.set_blocks(&static_objects, cube_size);
+            }
+            for center in result.explosions {
+                particles.explode(center);
+            }
+            particles.step(clock.step);
+        }
+        // El destello de una explosión reemplaza por un momento a la luz de la escena
+        physics.apply_flash(&mut light);
+
+        // Los bloques se posan desde su reposo en cada cuadro, así que la animación no se desvía
+        let mut rest_blocks: Vec<Cube> = static_objects.iter().cloned().chain(water.cubes(&water_material)).collect();
+        physics.dress(&mut rest_blocks);
+        let posed_blocks = animation.pose(&rest_blocks, t);
+
+        // Filtrar objetos dentro del frustum (las vistas panorámicas ven toda la escena)
+        let cull = !camera.is_panoramic();
+        for (id, cube) in posed_blocks.iter().enumerate() {
+            if !cull || frustum.is_sphere_in_frustum(cube.position(), cube.size / 2.0) {
+                objects.push(Box::new(cube.clone()));
+                object_ids.push(id);
             }
         }
+        // Las partículas van al final y sin identificador: no se pueden seleccionar ni editar
+        let block_count = objects.len();
+        for particle in particles.objects(&camera.eye) {
+            if !cull || frustum.is_sphere_in_frustum(particle.position(), cube_size) {
+                objects.push(particle);
+            }
+        }
+
+        // Proyección: P pasa por perspectiva, ortográfica, equirectangular y ojo de pez; I e O aplican vistas isométrica y dimétrica
+        if window.is_key_pressed(Key::P, KeyRepeat::No) {
+            camera.cycle_projection();
+        }
+        if window.is_key_pressed(Key::I, KeyRepeat::No) {
+            camera.apply_preset(ViewPreset::Isometric);
+        }
+        if window.is_key_pressed(Key::O, KeyRepeat::No) {
+            camera.apply_preset(ViewPreset::Dimetric);
+        }
 
-        for (i, cube) in water_cubes.iter_mut().enumerate() {
-            let animated_y = cube.position().y + 0.05 * (0.1 * ((t + i as f32).sin()));
-            cube.set_position(Vec3::new(cube.position().x, animated_y, cube.position().z));
+        // G pasa por las vistas de depuración
+        if window.is_key_pressed(Key::G, KeyRepeat::No) {
+            render_settings.debug_view = render_settings.debug_view.cycle();
+        }
 
-            if frustum.is_sphere_in_frustum(cube.position(), cube_size / 2.0) {
-                objects.push(Box::new(cube.clone()));
+        // V pasa por mono, anaglifo, lado a lado y arriba/abajo
+        if window.is_key_pressed(Key::V, KeyRepeat::No) {
+            camera.cycle_stereo();
+        }
+
+        if window.is_key_pressed(Key::K, KeyRepeat::No) {
+            // El tiempo del keyframe es el tiempo real transcurrido desde el anterior; tras cargar
+            // un recorrido, el primero que se graba va un rato después del último cargado
+            let time = match last_keyframe_instant {
+                _ if camera_path.keyframes.is_empty() => 0.0,
+                Some(instant) => camera_path.duration() + instant.elapsed().as_secs_f32(),
+                None => camera_path.duration() + loaded_key_gap,
+            };
+            camera_path.record(&camera, time);
+            last_keyframe_instant = Some(Instant::now());
+        }
+        if window.is_key_pressed(Key::J, KeyRepeat::No) {
+            playback_start = match playback_start {
+                None if !camera_path.keyframes.is_empty() => Some(Instant::now()),
+                _ => None,
+            };
+        }
+        if window.is_key_pressed(Key::L, KeyRepeat::No) {
+            match camera_path.save(&camera_path_file) {
+                Ok(()) => println!("Recorrido guardado en {}", camera_path_file),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        if window.is_key_pressed(Key::Backspace, KeyRepeat::No) {
+            camera_path.keyframes.clear();
+            last_keyframe_instant = None;
+            playback_start = None;
+        }
+        if let Some(start) = playback_start {
+            let time = start.elapsed().as_secs_f32();
+            camera_path.apply(time, &mut camera);
+            if time > camera_path.duration() {
+                playback_start = None;
+            }
+        }
+
+        if window.is_key_pressed(Key::T, KeyRepeat::No) {
+            turntable_spin = !turntable_spin;
+            if turntable_spin {
+                camera.center = scene_center;
+            }
+        }
+        if turntable_spin {
+            camera.orbit(turntable_speed * frame_seconds, 0.0);
+        }
+
+        // Zoom: - abre el campo de visión, = lo cierra
+        if window.is_key_down(Key::Minus) {
+            camera.zoom(zoom_speed * frame_seconds);
+        }
+        if window.is_key_down(Key::Equal) {
+            camera.zoom(-zoom_speed * frame_seconds);
+        }
+
+        // Profundidad de campo: [ y ] cambian la apertura, F enfoca el bloque del centro
+        if window.is_key_down(Key::LeftBracket) {
+            camera.aperture = (camera.aperture - aperture_speed * frame_seconds).max(0.0);
+        }
+        if window.is_key_down(Key::RightBracket) {
+            camera.aperture = (camera.aperture + aperture_speed * frame_seconds).min(max_aperture);
+        }
+        if window.is_key_pressed(Key::F, KeyRepeat::No) {
+            let hit = center_intersect(&objects[..block_count], &camera);
+            if hit.is_intersecting {
+                camera.focus_on(&hit.point);
+            }
+        }
+
+        if window.is_key_pressed(Key::N, KeyRepeat::No) {
+            denoiser.enabled = !denoiser.enabled;
+        }
+        if window.is_key_pressed(Key::Comma, KeyRepeat::Yes) {
+            denoiser.adjust_strength(-denoise_step);
+        }
+        if window.is_key_pressed(Key::Period, KeyRepeat::Yes) {
+            denoiser.adjust_strength(denoise_step);
+        }
+
+        // B activa la acumulación progresiva con la vista quieta
+        if window.is_key_pressed(Key::B, KeyRepeat::No) {
+            accumulator.enabled = !accumulator.enabled;
+        }
+
+        // U alterna el filtro de ampliación (vecino más cercano o bilineal)
+        if window.is_key_pressed(Key::U, KeyRepeat::No) {
+            resolution.toggle_filter();
+        }
+
+        // Con la vista quieta se renderiza siempre a resolución completa para poder acumular;
+        // si no, la escala sigue al tiempo de cuadro suavizado
+        // Con animación, agua corriendo, partículas o una TNT encendida la escena cambia aunque no se mueva ningún centro.
+        // Con la acumulación activa las partículas quedan fuera de la firma: el magma nunca deja de echar chispas
+        // y la imagen no convergería nunca; se promedian como un rastro hasta que la vista converge.
+        let particles_moving = particles.count() > 0 && !accumulator.enabled;
+        let moving = !animation.is_empty() || !water.settled || particles_moving || physics.is_active();
+        let signed = if accumulator.enabled { &objects[..block_count] } else { objects.as_slice() };
+        let signature = view_signature(&camera, render_settings.debug_view, &light, signed, if moving { t } else { 0.0 });
+        let still = accumulator.enabled && accumulator.is_still(signature);
+        let (scaled_width, scaled_height) = if still {
+            (width, height)
+        } else {
+            resolution.update(frame_seconds);
+            resolution.render_size(width, height)
+        };
+        if framebuffer.width != scaled_width || framebuffer.height != scaled_height {
+            framebuffer = Framebuffer::new(scaled_width, scaled_height);
+        }
+
+        let denoise = denoiser.enabled && camera.stereo == StereoMode::Mono;
+        // El contorno de selección (y el del bloque apuntado al editar) también se guía por ellos
+        let outline = camera.stereo == StereoMode::Mono && (editor.enabled || !selection.is_empty());
+        if (denoise || outline) && framebuffer.aux.is_none() {
+            // El filtro necesita los buffers auxiliares del próximo render
+            framebuffer.enable_aux();
+            accumulator.reset();
+        }
+        if !denoise && !outline {
+            framebuffer.aux = None;
+        }
+
+        accumulator.begin(scaled_width, scaled_height, signature);
+        if accumulator.is_converged() {
+            // Imagen convergida: no hace falta volver a renderizar
+            accumulator.resolve(&mut framebuffer);
+        } else {
+            let settings = RenderSettings { jitter: accumulator.jitter(), ..render_settings };
+            render(&mut framebuffer, objects.as_slice(), &object_ids, &camera, &light, &settings);
+            accumulator.add(&mut framebuffer);
+        }
+        if denoise {
+            denoiser.apply(&mut framebuffer);
+        }
+
+        // F1..F5 activan exposición, bloom, viñeta, contraste/saturación y LUT;
+        // F6 alterna la exposición automática dentro del efecto de exposición
+        let effect_keys = [Key::F1, Key::F2, Key::F3, Key::F4, Key::F5];
+        for (key, effect) in effect_keys.iter().zip(Effect::ALL.iter()) {
+            if window.is_key_pressed(*key, KeyRepeat::No) {
+                post.toggle(*effect);
+            }
+        }
+        if window.is_key_pressed(Key::F6, KeyRepeat::No) {
+            let auto_exposure = !post.auto_exposure();
+            post.set_auto_exposure(auto_exposure);
+        }
+        // El posprocesado se aplica sobre una copia para no tocar lo acumulado
+        let mut processed = Framebuffer::new(framebuffer.width, framebuffer.height);
+        processed.buffer.copy_from_slice(&framebuffer.buffer);
+        post.apply(&mut processed, frame_seconds);
+
+        // Objeto bajo el ratón, con el identificador estable en lugar del índice en `objects`
+        let window_size = (width as f32, height as f32);
+        let pick_at = |position: (f32, f32)| -> Option<Pick> {
+            pick(&objects[..block_count], &camera, position, window_size).map(|mut hit| {
+                hit.object_index = object_ids[hit.object_index];
+                hit
+            })
+        };
+
+        // Se resaltan los bloques seleccionados; editando, el bloque bajo el ratón
+        if let Some(aux) = &framebuffer.aux {
+            let highlighted = if editor.enabled {
+                Selection { ids: mouse_pos.and_then(pick_at).map(|hit| hit.object_index).into_iter().collect() }
+            } else {
+                selection.clone()
+            };
+            highlighted.draw_outline(&mut processed, aux);
+        }
+
+        // El render reducido se amplía al tamaño de la ventana; el panel se dibuja encima a resolución completa
+        upscale(&processed, &mut window_buffer, resolution.filter);
+
+        // H muestra el panel con tiempos, escala, rayos, cámara y el bloque bajo el ratón
+        if window.is_key_pressed(Key::H, KeyRepeat::No) {
+            hud.enabled = !hud.enabled;
+        }
+        let ray_stats = stats::take();
+        hud.record_frame(frame_seconds);
+
+        // E alterna el modo de edición. Editando, el clic izquierdo quita el bloque y el derecho
+        // pone uno del material activo contra la cara apuntada; si no, el clic izquierdo inspecciona y
+        // selecciona (con Shift agrega o quita de la selección; clic en el vacío cierra el inspector
+        // y vacía la selección). Los cambios se ven desde el próximo cuadro.
+        if window.is_key_pressed(Key::E, KeyRepeat::No) {
+            editor.enabled = !editor.enabled;
+        }
+        let left_down = window.get_mouse_down(MouseButton::Left);
+        let right_down = window.get_mouse_down(MouseButton::Right);
+        let left_clicked = left_down && !left_was_down;
+        // En vuelo libre el botón derecho también mira, así que el bloque se pone al soltarlo sin
+        // haberlo arrastrado; en órbita, al apretarlo
+        let right_clicked = match camera.mode {
+            CameraMode::Fly => !right_down && right_was_down && !right_dragged,
+            CameraMode::Orbit => right_down && !right_was_down,
+        };
+        left_was_down = left_down;
+        right_was_down = right_down;
+        let middle_down = window.get_mouse_down(MouseButton::Middle);
+        let middle_clicked = middle_down && !middle_was_down;
+        middle_was_down = middle_down;
+
+        // Clic del medio sobre una TNT: encender la mecha (con o sin modo de edición)
+        if middle_clicked {
+            if let Some(hit) = mouse_pos.and_then(pick_at).filter(|hit| hit.object_index < static_objects.len()) {
+                physics.ignite(&static_objects[hit.object_index]);
+            }
+        }
+
+        if editor.enabled {
+            let slot_keys = [Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9, Key::Key0];
+            for (slot, key) in slot_keys.iter().enumerate() {
+                if window.is_key_pressed(*key, KeyRepeat::No) {
+                    editor.select(slot);
+                }
+            }
+            if window.is_key_pressed(Key::Tab, KeyRepeat::No) {
+                editor.next_slot();
+            }
+
+            // Los cubos de agua están animados y no se editan
+            let mut edits = Vec::new();
+            if left_clicked {
+                if let Some(hit) = mouse_pos.and_then(pick_at).filter(|hit| hit.object_index < static_objects.len()) {
+                    edits.extend(editor.remove(&mut static_objects, hit.object_index));
+                }
+            }
+            if right_clicked {
+                if let Some(hit) = mouse_pos.and_then(pick_at) {
+                    edits.extend(editor.place(&mut static_objects, hit.point, hit.normal, cube_size));
+                }
+            }
+
+            // Ctrl+Z deshace, Ctrl+Y rehace, Ctrl+S guarda la escena
+            if ctrl_down && window.is_key_pressed(Key::Z, KeyRepeat::Yes) {
+                edits.extend(editor.undo(&mut static_objects));
+            }
+            if ctrl_down && window.is_key_pressed(Key::Y, KeyRepeat::Yes) {
+                edits.extend(editor.redo(&mut static_objects));
+            }
+            // Las pistas de animación siguen a sus bloques
+            for edit in &edits {
+                if let BlockEdit::Removed(index) = edit {
+                    animation.block_removed(*index);
+                }
+            }
+            // Editar cambia los índices de los bloques, así que la selección ya no vale,
+            // y el agua tiene que ver los bloques nuevos
+            if !edits.is_empty() {
+                selection.clear();
+                inspected = None;
+                water.set_solids(&static_objects);
+                particles.set_blocks(&static_objects, cube_size);
+            }
+            if ctrl_down && window.is_key_pressed(Key::S, KeyRepeat::No) {
+                let path = scene_path.clone().unwrap_or_else(|| "scene.txt".to_string());
+                let blocks: Vec<Cube> = static_objects.iter().chain(water_sources.iter()).cloned().collect();
+                match scene::save_scene(&path, &camera, &blocks, &animation.tracks) {
+                    Ok(()) => println!("Escena guardada en {}", path),
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+            editor.draw_hotbar(&mut window_buffer);
+        } else if left_clicked {
+            let shift_down = window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift);
+            inspected = mouse_pos.and_then(pick_at);
+            match &inspected {
+                Some(hit) if shift_down => selection.toggle(hit.object_index),
+                Some(hit) => selection.set(hit.object_index),
+                None if shift_down => {}
+                None => selection.clear(),
             }
         }
 
-        render(&mut framebuffer, objects.as_slice(), &camera, &light);
+        if let Some(hit) = &inspected {
+            let lines = [
+                format!("BLOCK #{}", hit.object_index),
+                format!("MATERIAL {} (ID {})", hit.material.name, hit.material.id),
+                format!("POINT {:.2} {:.2} {:.2}", hit.point.x, hit.point.y, hit.point.z),
+                format!("NORMAL {:.0} {:.0} {:.0}", hit.normal.x, hit.normal.y, hit.normal.z),
+                format!("UV {:.2} {:.2}", hit.uv.0, hit.uv.1),
+                format!("DISTANCE {:.2}", hit.distance),
+                format!("SELECTED {}", selection.ids.len()),
+            ];
+            let (panel_width, _) = panel_size(&lines);
+            draw_panel(&mut window_buffer, width.saturating_sub(panel_width), 0, &lines);
+        }
 
-        window.update_with_buffer(&framebuffer.buffer, scaled_width, scaled_height).unwrap();
+        if hud.enabled {
+            let hovered_text = match mouse_pos.and_then(pick_at) {
+                Some(hit) => format!(
+                    "HOVER #{} {} AT {:.1} {:.1} {:.1}",
+                    hit.object_index, hit.material.name, hit.point.x, hit.point.y, hit.point.z
+                ),
+                None => "HOVER -".to_string(),
+            };
+            let frame_time = resolution.smoothed_frame_time;
+            let lines = [
+                format!("FRAME {:.1} MS ({:.0} FPS)", frame_time * 1000.0, 1.0 / frame_time.max(1e-6)),
+                format!(
+                    "SCALE {:.0}% {}X{} ACC {}/{}",
+                    scaled_width as f32 / width as f32 * 100.0, scaled_width, scaled_height,
+                    accumulator.samples, accumulator.max_samples
+                ),
+                // Solo la vista de sombras traza rayos de sombra; el sombreado normal no los usa
+                if render_settings.debug_view == DebugView::ShadowOcclusion {
+                    format!(
+                        "RAYS {} (P {} S {} SH {})",
+                        short_count(ray_stats.total_rays()), short_count(ray_stats.primary_rays),
+                        short_count(ray_stats.secondary_rays), short_count(ray_stats.shadow_rays)
+                    )
+                } else {
+                    format!(
+                        "RAYS {} (P {} S {})",
+                        short_count(ray_stats.total_rays()), short_count(ray_stats.primary_rays),
+                        short_count(ray_stats.secondary_rays)
+                    )
+                },
+                format!("TESTS {}", short_count(ray_stats.intersection_tests)),
+                format!(
+                    "SIM {:.2}S X{}{}",
+                    clock.time, clock.time_scale, if clock.paused { " PAUSED" } else { "" }
+                ),
+                if particles.enabled {
+                    format!("PARTICLES {}", particles.count())
+                } else {
+                    "PARTICLES OFF".to_string()
+                },
+                if day_cycle.enabled {
+                    format!("TIME {:02}:{:02}", day_cycle.hour as u32, (day_cycle.hour.fract() * 60.0) as u32)
+                } else {
+                    "TIME FIXED".to_string()
+                },
+                format!("EYE {:.2} {:.2} {:.2}", camera.eye.x, camera.eye.y, camera.eye.z),
+                format!("CENTER {:.2} {:.2} {:.2}", camera.center.x, camera.center.y, camera.center.z),
+                format!("FOV {:.1} {:?} VIEW {}", camera.fov, camera.projection, render_settings.debug_view.name()),
+                hovered_text,
+            ];
+            hud.draw(&mut window_buffer, &lines);
+        }
 
-        t += 0.03;
+        window.update_with_buffer(&window_buffer.buffer, width, height).unwrap();
     }
 }
@@ -30,6 +30,10 @@ impl Texture {
     }
 }
 
+// ID de los materiales que no están en la lista cargada (partículas, el material de "sin impacto"),
+// para que no se confundan con el musgo (ID 0) en la vista de IDs ni en los buffers exportados
+pub const NO_MATERIAL_ID: usize = usize::MAX;
+
 #[derive(Debug, Clone)]
 pub struct Material {
     pub diffuse: Color,
@@ -39,6 +43,10 @@ pub struct Material {
     pub transparency: f32,
     pub texture: Option<Arc<Texture>>, // Usa Arc para almacenar la textura
     pub has_texture: bool,              // Indicador de si el material tiene textura
+    pub id: usize,                      // Índice del material en la lista de materiales cargados
+    pub name: &'static str,             // Nombre corto para mostrar y para los archivos de escena
+    pub emission: f32,                  // Brillo propio, como fracción del color difuso (0 = no emite)
+    pub falls: bool,                    // Cae mientras tenga aire debajo, como la arena
 }
 
 impl Material {
@@ -59,6 +67,10 @@ impl Material {
             transparency,
             texture,
             has_texture,
+            id: NO_MATERIAL_ID,
+            name: "",
+            emission: 0.0,
+            falls: false,
         }
     }
 
@@ -71,6 +83,10 @@ impl Material {
             transparency: 0.0,
             texture: None,
             has_texture: false,
+            id: NO_MATERIAL_ID,
+            name: "",
+            emission: 0.0,
+            falls: false,
         }
     }
 
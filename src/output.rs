@@ -0,0 +1,445 @@
+use nalgebra_glm::Vec3;
+use crate::framebuffer::{AuxBuffers, Framebuffer, NO_HIT};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::codecs::png::PngEncoder;
+use image::{ColorType, Delay, DynamicImage, Frame};
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+// Ruta de un cuadro de una secuencia: la primera racha de `#` se reemplaza por el número
+// de cuadro con ceros a la izquierda (`frames/frame_####.png` -> `frames/frame_0007.png`).
+// Si el patrón no tiene `#`, el número se agrega antes de la extensión.
+pub fn numbered_path(pattern: &str, index: usize) -> String {
+    if let Some(start) = pattern.find('#') {
+        let digits = pattern[start..].chars().take_while(|&c| c == '#').count();
+        return format!("{}{:0width$}{}", &pattern[..start], index, &pattern[start + digits..], width = digits);
+    }
+
+    // Solo cuenta la extensión del nombre del archivo, no un punto en los directorios (`./out`)
+    let extension = Path::new(pattern).extension().and_then(|e| e.to_str());
+    match extension.and_then(|e| Some((pattern.strip_suffix(e)?.strip_suffix('.')?, e))) {
+        Some((stem, extension)) => format!("{}_{:04}.{}", stem, index, extension),
+        None => format!("{}_{:04}", pattern, index),
+    }
+}
+
+// Destino de una animación que recibe los cuadros uno por uno
+pub trait FrameWriter {
+    fn write_frame(&mut self, framebuffer: &Framebuffer) -> Result<(), String>;
+    // Cierra el archivo (escribe lo que falte al final)
+    fn finish(&mut self) -> Result<(), String>;
+}
+
+// Abrir el escritor de animación según la extensión: `.gif`, `.png`/`.apng` o `.y4m`.
+// Con `-` los cuadros se escriben en la salida estándar como Y4M, para encadenar con un encoder externo.
+pub fn open_animation(path: &str, width: usize, height: usize, frame_count: usize, fps: f32) -> Result<Box<dyn FrameWriter>, String> {
+    if path == "-" {
+        return Ok(Box::new(Y4mWriter::new(BufWriter::new(io::stdout()), width, height, fps)?));
+    }
+
+    let file = File::create(path).map_err(|e| format!("No se pudo crear {}: {}", path, e))?;
+    let writer = BufWriter::new(file);
+    let extension = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+
+    match extension.as_str() {
+        "gif" => Ok(Box::new(GifWriter::new(writer, fps))),
+        "png" | "apng" => Ok(Box::new(ApngWriter::new(writer, width, height, frame_count, fps)?)),
+        "y4m" => Ok(Box::new(Y4mWriter::new(writer, width, height, fps)?)),
+        other => Err(format!("Formato de animación no soportado '.{}'", other)),
+    }
+}
+
+// Reparte cada cuadro renderizado entre una secuencia de PNG numerados y una animación
+pub struct FrameSink {
+    pattern: Option<String>,
+    animation: Option<Box<dyn FrameWriter>>,
+    aux: Option<(String, AuxFormat)>, // Prefijo numerado para los buffers auxiliares de cada cuadro
+}
+
+impl FrameSink {
+    pub fn new(pattern: Option<String>, animation_path: Option<String>, width: usize, height: usize, frame_count: usize, fps: f32) -> Result<Self, String> {
+        let animation = match animation_path {
+            Some(path) => Some(open_animation(&path, width, height, frame_count, fps)?),
+            None => None,
+        };
+        Ok(FrameSink { pattern, animation, aux: None })
+    }
+
+    // Exportar también los buffers auxiliares de cada cuadro con `prefix` numerado
+    pub fn with_aux(mut self, prefix: String, format: AuxFormat) -> Self {
+        self.aux = Some((prefix, format));
+        self
+    }
+
+    pub fn push(&mut self, index: usize, framebuffer: &Framebuffer) -> Result<(), String> {
+        if let Some(pattern) = &self.pattern {
+            framebuffer.save(numbered_path(pattern, index))?;
+        }
+        if let Some(animation) = self.animation.as_mut() {
+            animation.write_frame(framebuffer)?;
+        }
+        if let Some((prefix, format)) = &self.aux {
+            save_aux_buffers(framebuffer, &numbered_path(prefix, index), *format)?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> Result<(), String> {
+        match self.animation.as_mut() {
+            Some(animation) => animation.finish(),
+            None => Ok(()),
+        }
+    }
+}
+
+// GIF animado; cada cuadro se cuantiza a una paleta de 256 colores con NeuQuant
+pub struct GifWriter<W: Write> {
+    encoder: Option<GifEncoder<SharedWriter<W>>>,
+    output: Rc<RefCell<GifOutput<W>>>,
+    delay: Delay,
+}
+
+// El encoder solo escribe el final del GIF al destruirse y descarta los errores de esa escritura,
+// así que escribe a través de un destino compartido que guarda el primer error y sigue
+// accesible para vaciarlo después
+struct GifOutput<W: Write> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+struct SharedWriter<W: Write>(Rc<RefCell<GifOutput<W>>>);
+
+impl<W: Write> Write for SharedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut output = self.0.borrow_mut();
+        output.writer.write(buf).inspect_err(|e| {
+            output.error.get_or_insert_with(|| io::Error::new(e.kind(), e.to_string()));
+        })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.borrow_mut().writer.flush()
+    }
+}
+
+// Velocidad de NeuQuant (1 = mejor paleta, 30 = más rápido)
+const GIF_QUANTIZATION_SPEED: i32 = 10;
+
+impl<W: Write> GifWriter<W> {
+    pub fn new(writer: W, fps: f32) -> Self {
+        let output = Rc::new(RefCell::new(GifOutput { writer, error: None }));
+        let mut encoder = GifEncoder::new_with_speed(SharedWriter(output.clone()), GIF_QUANTIZATION_SPEED);
+        encoder.set_repeat(Repeat::Infinite).ok();
+        GifWriter {
+            encoder: Some(encoder),
+            output,
+            delay: Delay::from_numer_denom_ms(1000, fps.max(1.0).round() as u32),
+        }
+    }
+}
+
+impl<W: Write> FrameWriter for GifWriter<W> {
+    fn write_frame(&mut self, framebuffer: &Framebuffer) -> Result<(), String> {
+        let encoder = self.encoder.as_mut().ok_or("El GIF ya está cerrado")?;
+        let rgba = DynamicImage::ImageRgb8(framebuffer.to_rgb_image()).into_rgba8();
+        encoder
+            .encode_frame(Frame::from_parts(rgba, 0, 0, self.delay))
+            .map_err(|e| format!("Error al escribir el GIF: {}", e))
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        // Destruir el encoder escribe el final; después se vacía el archivo
+        drop(self.encoder.take());
+        let mut output = self.output.borrow_mut();
+        if let Some(e) = output.error.take() {
+            return Err(format!("Error al escribir el GIF: {}", e));
+        }
+        output.writer.flush().map_err(|e| format!("Error al escribir el GIF: {}", e))
+    }
+}
+
+// PNG animado. La cantidad de cuadros va en la cabecera, así que se fija al crearlo.
+pub struct ApngWriter<W: Write> {
+    writer: W,
+    width: usize,
+    height: usize,
+    frame_count: usize,
+    frames_written: usize,
+    sequence: u32,
+    delay_num: u16,
+    delay_den: u16,
+}
+
+impl<W: Write> ApngWriter<W> {
+    pub fn new(mut writer: W, width: usize, height: usize, frame_count: usize, fps: f32) -> Result<Self, String> {
+        writer.write_all(b"\x89PNG\r\n\x1a\n").map_err(io_error)?;
+
+        // IHDR: RGB de 8 bits, sin entrelazado (igual que los cuadros del encoder de PNG)
+        let mut ihdr = Vec::with_capacity(13);
+        ihdr.extend_from_slice(&(width as u32).to_be_bytes());
+        ihdr.extend_from_slice(&(height as u32).to_be_bytes());
+        ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+        write_chunk(&mut writer, b"IHDR", &ihdr)?;
+
+        // acTL: cantidad de cuadros y repeticiones (0 = infinitas)
+        let mut actl = Vec::with_capacity(8);
+        actl.extend_from_slice(&(frame_count as u32).to_be_bytes());
+        actl.extend_from_slice(&0u32.to_be_bytes());
+        write_chunk(&mut writer, b"acTL", &actl)?;
+
+        Ok(ApngWriter {
+            writer,
+            width,
+            height,
+            frame_count,
+            frames_written: 0,
+            sequence: 0,
+            // Demora de cada cuadro en segundos: delay_num / delay_den
+            delay_num: 100,
+            delay_den: (fps.max(1.0) * 100.0).round().min(u16::MAX as f32) as u16,
+        })
+    }
+}
+
+impl<W: Write> FrameWriter for ApngWriter<W> {
+    fn write_frame(&mut self, framebuffer: &Framebuffer) -> Result<(), String> {
+        if framebuffer.width != self.width || framebuffer.height != self.height {
+            return Err("Todos los cuadros del APNG deben tener el mismo tamaño".to_string());
+        }
+        if self.frames_written >= self.frame_count {
+            return Err("Se escribieron más cuadros de los declarados en el APNG".to_string());
+        }
+
+        // fcTL: cuadro completo en (0, 0), sin disposición ni mezcla
+        let mut fctl = Vec::with_capacity(26);
+        fctl.extend_from_slice(&self.sequence.to_be_bytes());
+        fctl.extend_from_slice(&(self.width as u32).to_be_bytes());
+        fctl.extend_from_slice(&(self.height as u32).to_be_bytes());
+        fctl.extend_from_slice(&0u32.to_be_bytes());
+        fctl.extend_from_slice(&0u32.to_be_bytes());
+        fctl.extend_from_slice(&self.delay_num.to_be_bytes());
+        fctl.extend_from_slice(&self.delay_den.to_be_bytes());
+        fctl.extend_from_slice(&[0, 0]);
+        write_chunk(&mut self.writer, b"fcTL", &fctl)?;
+        self.sequence += 1;
+
+        // Los datos comprimidos salen de un PNG normal del cuadro
+        let image_data = encode_idat(framebuffer)?;
+        if self.frames_written == 0 {
+            write_chunk(&mut self.writer, b"IDAT", &image_data)?;
+        } else {
+            let mut fdat = Vec::with_capacity(image_data.len() + 4);
+            fdat.extend_from_slice(&self.sequence.to_be_bytes());
+            fdat.extend_from_slice(&image_data);
+            write_chunk(&mut self.writer, b"fdAT", &fdat)?;
+            self.sequence += 1;
+        }
+
+        self.frames_written += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        if self.frames_written != self.frame_count {
+            return Err(format!(
+                "El APNG declara {} cuadros pero se escribieron {}",
+                self.frame_count, self.frames_written
+            ));
+        }
+        write_chunk(&mut self.writer, b"IEND", &[])?;
+        self.writer.flush().map_err(io_error)
+    }
+}
+
+// Video YUV4MPEG2 sin compresión (4:2:0, BT.601 de rango completo). No necesita conocer
+// la cantidad de cuadros, así que sirve para transmitir a un archivo o a la salida estándar.
+pub struct Y4mWriter<W: Write> {
+    writer: W,
+    width: usize,
+    height: usize,
+}
+
+impl<W: Write> Y4mWriter<W> {
+    pub fn new(mut writer: W, width: usize, height: usize, fps: f32) -> Result<Self, String> {
+        // La tasa de cuadros va como fracción: 29.97 -> 2997:100
+        let header = format!(
+            "YUV4MPEG2 W{} H{} F{}:100 Ip A1:1 C420jpeg\n",
+            width,
+            height,
+            (fps.max(0.01) * 100.0).round() as u32
+        );
+        writer.write_all(header.as_bytes()).map_err(io_error)?;
+        Ok(Y4mWriter { writer, width, height })
+    }
+}
+
+impl<W: Write> FrameWriter for Y4mWriter<W> {
+    fn write_frame(&mut self, framebuffer: &Framebuffer) -> Result<(), String> {
+        if framebuffer.width != self.width || framebuffer.height != self.height {
+            return Err("Todos los cuadros del video deben tener el mismo tamaño".to_string());
+        }
+
+        let (w, h) = (self.width, self.height);
+        let (cw, ch) = (w.div_ceil(2), h.div_ceil(2));
+        let mut y_plane = Vec::with_capacity(w * h);
+        let mut u_sum = vec![0.0f32; cw * ch];
+        let mut v_sum = vec![0.0f32; cw * ch];
+        let mut count = vec![0.0f32; cw * ch];
+
+        for (index, pixel) in framebuffer.buffer.iter().enumerate() {
+            let r = ((pixel >> 16) & 0xff) as f32;
+            let g = ((pixel >> 8) & 0xff) as f32;
+            let b = (pixel & 0xff) as f32;
+            y_plane.push((0.299 * r + 0.587 * g + 0.114 * b).round() as u8);
+
+            // La crominancia se promedia en bloques de 2x2
+            let chroma = (index / w / 2) * cw + (index % w) / 2;
+            u_sum[chroma] += -0.168_736 * r - 0.331_264 * g + 0.5 * b + 128.0;
+            v_sum[chroma] += 0.5 * r - 0.418_688 * g - 0.081_312 * b + 128.0;
+            count[chroma] += 1.0;
+        }
+
+        let average = |sum: &[f32]| -> Vec<u8> {
+            sum.iter().zip(count.iter()).map(|(s, n)| (s / n).round().clamp(0.0, 255.0) as u8).collect()
+        };
+
+        self.writer.write_all(b"FRAME\n").map_err(io_error)?;
+        self.writer.write_all(&y_plane).map_err(io_error)?;
+        self.writer.write_all(&average(&u_sum)).map_err(io_error)?;
+        self.writer.write_all(&average(&v_sum)).map_err(io_error)
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.writer.flush().map_err(io_error)
+    }
+}
+
+// Formato de exportación de los buffers auxiliares
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuxFormat {
+    Png, // Visualizaciones de 8 bits (profundidad normalizada, IDs con colores)
+    Pfm, // Valores en punto flotante sin pérdida (Portable Float Map)
+}
+
+// Exportar cada buffer auxiliar como `<prefijo>_<canal>.<ext>`:
+// depth, normal, albedo, uv, material y object
+pub fn save_aux_buffers(framebuffer: &Framebuffer, prefix: &str, format: AuxFormat) -> Result<(), String> {
+    let aux = framebuffer.aux.as_ref().ok_or("El framebuffer no tiene buffers auxiliares")?;
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    let id_value = |id: u32| if id == NO_HIT { -1.0 } else { id as f32 };
+
+    let channels: [(&str, Vec<Vec3>, bool); 6] = [
+        ("depth", aux.depth.iter().map(|&d| Vec3::new(d, d, d)).collect(), true),
+        ("normal", aux.normal.clone(), false),
+        ("albedo", aux.albedo.clone(), false),
+        ("uv", aux.uv.iter().map(|&(u, v)| Vec3::new(u, v, 0.0)).collect(), false),
+        ("material", aux.material_id.iter().map(|&id| Vec3::new(id_value(id), 0.0, 0.0)).collect(), true),
+        ("object", aux.object_id.iter().map(|&id| Vec3::new(id_value(id), 0.0, 0.0)).collect(), true),
+    ];
+
+    for (name, data, single_channel) in channels.iter() {
+        match format {
+            AuxFormat::Pfm => write_pfm(&format!("{}_{}.pfm", prefix, name), width, height, data, *single_channel)?,
+            AuxFormat::Png => {
+                let preview = aux_preview(name, aux, data);
+                let image = image::RgbImage::from_fn(width as u32, height as u32, |x, y| {
+                    image::Rgb(preview[y as usize * width + x as usize])
+                });
+                let path = format!("{}_{}.png", prefix, name);
+                image.save(&path).map_err(|e| format!("No se pudo guardar {}: {}", path, e))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Colores de 8 bits para ver un canal auxiliar como imagen
+fn aux_preview(name: &str, aux: &AuxBuffers, data: &[Vec3]) -> Vec<[u8; 3]> {
+    let to_byte = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    match name {
+        "depth" => {
+            // Cerca = blanco, lejos = negro, sin impacto = negro
+            let hits = aux.depth.iter().filter(|d| d.is_finite());
+            let near = hits.clone().fold(f32::INFINITY, |a, &b| a.min(b));
+            let far = hits.fold(0.0f32, |a, &b| a.max(b));
+            aux.depth.iter().map(|&d| {
+                let value = if d.is_finite() { 1.0 - (d - near) / (far - near).max(1e-6) } else { 0.0 };
+                [to_byte(value); 3]
+            }).collect()
+        }
+        "normal" => data.iter().map(|n| {
+            if n.magnitude() > 0.0 { [to_byte(n.x * 0.5 + 0.5), to_byte(n.y * 0.5 + 0.5), to_byte(n.z * 0.5 + 0.5)] } else { [0; 3] }
+        }).collect(),
+        "material" | "object" => data.iter().map(|v| id_color(v.x)).collect(),
+        _ => data.iter().map(|v| [to_byte(v.x), to_byte(v.y), to_byte(v.z)]).collect(),
+    }
+}
+
+// Color estable para un ID (negro si no hay impacto)
+pub fn id_color(id: f32) -> [u8; 3] {
+    if id < 0.0 {
+        return [0; 3];
+    }
+    let mut h = (id as u32).wrapping_add(1).wrapping_mul(0x9e37_79b9);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x85eb_ca6b);
+    h ^= h >> 13;
+    [(h >> 16) as u8 | 0x40, (h >> 8) as u8 | 0x40, h as u8 | 0x40]
+}
+
+// Portable Float Map: cabecera de texto y flotantes little-endian, filas de abajo hacia arriba
+fn write_pfm(path: &str, width: usize, height: usize, data: &[Vec3], single_channel: bool) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("No se pudo crear {}: {}", path, e))?;
+    let mut writer = BufWriter::new(file);
+    let kind = if single_channel { "Pf" } else { "PF" };
+    write!(writer, "{}\n{} {}\n-1.0\n", kind, width, height).map_err(io_error)?;
+
+    for y in (0..height).rev() {
+        for value in &data[y * width..(y + 1) * width] {
+            let components: &[f32] = if single_channel { &value.as_slice()[..1] } else { value.as_slice() };
+            for component in components {
+                writer.write_all(&component.to_le_bytes()).map_err(io_error)?;
+            }
+        }
+    }
+    writer.flush().map_err(io_error)
+}
+
+fn io_error(e: std::io::Error) -> String {
+    format!("Error de escritura: {}", e)
+}
+
+// Escribe un chunk PNG: largo, tipo, datos y CRC del tipo + datos
+fn write_chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> Result<(), String> {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(kind);
+    hasher.update(data);
+
+    writer.write_all(&(data.len() as u32).to_be_bytes()).map_err(io_error)?;
+    writer.write_all(kind).map_err(io_error)?;
+    writer.write_all(data).map_err(io_error)?;
+    writer.write_all(&hasher.finalize().to_be_bytes()).map_err(io_error)
+}
+
+// Codifica el cuadro como PNG en memoria y devuelve sus datos IDAT concatenados
+fn encode_idat(framebuffer: &Framebuffer) -> Result<Vec<u8>, String> {
+    let mut png = Vec::new();
+    PngEncoder::new(&mut png)
+        .encode(&framebuffer.to_rgb_image(), framebuffer.width as u32, framebuffer.height as u32, ColorType::Rgb8)
+        .map_err(|e| format!("Error al codificar el cuadro: {}", e))?;
+
+    let mut data = Vec::new();
+    let mut offset = 8; // Firma PNG
+    while offset + 8 <= png.len() {
+        let length = u32::from_be_bytes([png[offset], png[offset + 1], png[offset + 2], png[offset + 3]]) as usize;
+        let kind = &png[offset + 4..offset + 8];
+        if kind == b"IDAT" {
+            data.extend_from_slice(&png[offset + 8..offset + 8 + length]);
+        }
+        offset += length + 12;
+    }
+    Ok(data)
+}
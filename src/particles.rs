@@ -0,0 +1,358 @@
+use nalgebra_glm::Vec3;
+use crate::billboard::Billboard;
+use crate::color::Color;
+use crate::cube::Cube;
+use crate::editor::find_block;
+use crate::material::Material;
+use crate::ray_intersect::RayIntersect;
+use crate::sky::mix;
+use crate::sphere::Sphere;
+use std::f32::consts::TAU;
+use std::sync::Arc;
+
+// Cómo se dibuja una partícula
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    Sphere, // Esfera diminuta
+    Quad,   // Cuadrado que mira a la cámara
+    Cube,   // Cubito girado (escombros)
+}
+
+// Cómo nacen, se mueven y se ven las partículas de un emisor
+#[derive(Debug, Clone)]
+pub struct ParticleStyle {
+    pub shape: Shape,
+    pub rate: f32,                 // Partículas por segundo por cada punto de emisión
+    pub max_particles: usize,      // Tope de partículas vivas del emisor (cada una es un objeto más que trazar)
+    pub lifetime: (f32, f32),      // Vida mínima y máxima, en segundos
+    pub velocity: Vec3,            // Velocidad inicial media
+    pub spread: f32,               // Variación aleatoria de la velocidad inicial en cada eje
+    pub gravity: f32,              // Aceleración hacia abajo; negativa, la partícula flota hacia arriba
+    pub drag: f32,                 // Fracción de la velocidad que se pierde por segundo
+    pub flutter: f32,              // Vaivén lateral, en unidades por segundo (hojas que planean)
+    pub size: (f32, f32),          // Tamaño al nacer y al morir
+    pub color: (Color, Color),     // Color al nacer y al morir
+    pub transparency: (f32, f32),  // Transparencia al nacer y al morir (el humo se desvanece)
+    pub emission: f32,             // Brillo propio (las chispas se ven también de noche)
+}
+
+impl ParticleStyle {
+    // Chispas que saltan de la lava y caen apagándose
+    pub fn sparks() -> Self {
+        ParticleStyle {
+            shape: Shape::Sphere,
+            rate: 5.0,
+            max_particles: 24,
+            lifetime: (0.6, 1.1),
+            velocity: Vec3::new(0.0, 1.1, 0.0),
+            spread: 0.35,
+            gravity: 2.0,
+            drag: 0.2,
+            flutter: 0.0,
+            size: (0.06, 0.02),
+            color: (Color::new(255, 190, 60), Color::new(170, 30, 5)),
+            transparency: (0.0, 0.0),
+            emission: 1.0,
+        }
+    }
+
+    // Humo que sube despacio, se abre y se desvanece
+    pub fn smoke() -> Self {
+        ParticleStyle {
+            shape: Shape::Quad,
+            rate: 1.2,
+            max_particles: 10,
+            lifetime: (2.0, 3.0),
+            velocity: Vec3::new(0.0, 0.3, 0.0),
+            spread: 0.06,
+            gravity: -0.05,
+            drag: 0.3,
+            flutter: 0.05,
+            size: (0.12, 0.4),
+            color: (Color::new(70, 65, 60), Color::new(170, 170, 170)),
+            transparency: (0.35, 0.95),
+            emission: 0.0,
+        }
+    }
+
+    // Escombros que salen volando de una explosión; no nacen solos, solo en ráfagas
+    pub fn debris() -> Self {
+        ParticleStyle {
+            shape: Shape::Cube,
+            rate: 0.0,
+            max_particles: 40,
+            lifetime: (1.0, 1.6),
+            velocity: Vec3::new(0.0, 2.2, 0.0),
+            spread: 2.0,
+            gravity: 6.0,
+            drag: 0.3,
+            flutter: 0.0,
+            size: (0.09, 0.06),
+            color: (Color::new(130, 110, 85), Color::new(70, 60, 50)),
+            transparency: (0.0, 0.0),
+            emission: 0.0,
+        }
+    }
+
+    // Hojas que caen de la copa meciéndose de lado a lado y se secan al caer
+    pub fn leaves() -> Self {
+        ParticleStyle {
+            shape: Shape::Quad,
+            rate: 0.1,
+            max_particles: 12,
+            lifetime: (4.0, 6.0),
+            velocity: Vec3::new(0.0, -0.15, 0.0),
+            spread: 0.08,
+            gravity: 0.3,
+            drag: 1.5,
+            flutter: 0.35,
+            size: (0.08, 0.07),
+            color: (Color::new(110, 150, 45), Color::new(160, 130, 55)),
+            transparency: (0.0, 0.3),
+            emission: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Particle {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub age: f32,      // Segundos desde que nació
+    pub lifetime: f32, // Segundos que vive
+    phase: f32,        // Desfase del vaivén, en vueltas
+}
+
+impl Particle {
+    // Fracción de la vida ya consumida, en [0, 1]
+    pub fn life(&self) -> f32 {
+        (self.age / self.lifetime).clamp(0.0, 1.0)
+    }
+}
+
+// Fuente de partículas de un mismo estilo, repartida en varios puntos
+#[derive(Debug, Clone)]
+pub struct Emitter {
+    pub style: ParticleStyle,
+    pub sources: Vec<Vec3>, // Puntos de emisión
+    pub extent: Vec3,       // Media caja alrededor de cada punto donde puede nacer la partícula
+    pub particles: Vec<Particle>,
+    pending: f32,           // Fracción de partícula acumulada para el paso siguiente
+    rng: Rng,
+}
+
+impl Emitter {
+    pub fn new(style: ParticleStyle, sources: Vec<Vec3>, extent: Vec3, seed: u32) -> Self {
+        Emitter {
+            style,
+            sources,
+            extent,
+            particles: Vec::new(),
+            pending: 0.0,
+            rng: Rng::new(seed),
+        }
+    }
+
+    // Avanzar `dt` segundos: envejecer, mover y hacer nacer partículas
+    pub fn step(&mut self, dt: f32) {
+        let style = &self.style;
+        self.particles.retain_mut(|particle| {
+            particle.age += dt;
+            if particle.age >= particle.lifetime {
+                return false;
+            }
+            particle.velocity.y -= style.gravity * dt;
+            particle.velocity *= (1.0 - style.drag * dt).max(0.0);
+            let sway = style.flutter * (TAU * (particle.age * 0.5 + particle.phase)).sin();
+            particle.position += (particle.velocity + Vec3::new(sway, 0.0, sway * 0.5)) * dt;
+            true
+        });
+
+        if self.sources.is_empty() {
+            return;
+        }
+        self.pending += self.style.rate * self.sources.len() as f32 * dt;
+        while self.pending >= 1.0 {
+            self.pending -= 1.0;
+            if self.particles.len() < self.style.max_particles {
+                self.spawn();
+            }
+        }
+    }
+
+    // Hacer nacer `count` partículas de golpe alrededor de `at`, sin pasar del tope
+    pub fn burst(&mut self, at: Vec3, count: usize) {
+        for _ in 0..count {
+            if self.particles.len() >= self.style.max_particles {
+                break;
+            }
+            self.spawn_at(at);
+        }
+    }
+
+    fn spawn(&mut self) {
+        let source = self.sources[self.rng.below(self.sources.len())];
+        self.spawn_at(source);
+    }
+
+    fn spawn_at(&mut self, source: Vec3) {
+        let offset = Vec3::new(self.rng.signed(), self.rng.signed(), self.rng.signed()).component_mul(&self.extent);
+        let jitter = Vec3::new(self.rng.signed(), self.rng.signed(), self.rng.signed()) * self.style.spread;
+        let (min_life, max_life) = self.style.lifetime;
+        self.particles.push(Particle {
+            position: source + offset,
+            velocity: self.style.velocity + jitter,
+            age: 0.0,
+            lifetime: min_life + (max_life - min_life) * self.rng.unit(),
+            phase: self.rng.unit(),
+        });
+    }
+
+    // Objetos para render de las partículas vivas; los cuadrados miran hacia `eye`
+    pub fn objects(&self, eye: &Vec3) -> Vec<Box<dyn RayIntersect>> {
+        let style = &self.style;
+        self.particles
+            .iter()
+            .map(|particle| {
+                let life = particle.life();
+                let size = style.size.0 + (style.size.1 - style.size.0) * life;
+                let transparency = style.transparency.0 + (style.transparency.1 - style.transparency.0) * life;
+                // Lo que brilla por sí mismo casi no refleja luz, así no se satura a blanco
+                let diffuse = 0.9 * (1.0 - style.emission).max(0.0);
+                let mut material = Material::new(
+                    mix(style.color.0, style.color.1, life),
+                    10.0,
+                    [diffuse, 0.1, 0.0, transparency],
+                    1.0,
+                    0.0,
+                    None,
+                    false,
+                );
+                material.name = "particle";
+                material.emission = style.emission;
+                let material = Arc::new(material);
+                match style.shape {
+                    Shape::Sphere => Box::new(Sphere::new(particle.position, size / 2.0, material)) as Box<dyn RayIntersect>,
+                    Shape::Quad => Box::new(Billboard::facing(particle.position, size, eye, material)),
+                    Shape::Cube => {
+                        let mut cube = Cube::new(particle.position, size, material);
+                        cube.rotation = (particle.phase + particle.age) * TAU;
+                        Box::new(cube)
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+// Todos los emisores de la escena, avanzados con el reloj de la simulación
+#[derive(Debug, Clone)]
+pub struct ParticleSystem {
+    pub enabled: bool,
+    pub emitters: Vec<Emitter>,
+    pub debris: Emitter, // Escombros de las explosiones
+}
+
+impl ParticleSystem {
+    // Emisores a partir de los bloques: chispas y humo sobre la lava, hojas bajo la copa del árbol
+    pub fn for_blocks(blocks: &[Cube], cube_size: f32) -> Self {
+        let top = |cube: &Cube| cube.position() + Vec3::new(0.0, cube_size / 2.0, 0.0);
+        let magma: Vec<Vec3> = blocks.iter().filter(|cube| cube.material.name == "magma").map(top).collect();
+        // Solo las hojas sin nada debajo dejan caer hojas sueltas
+        let leaves: Vec<Vec3> = blocks
+            .iter()
+            .filter(|cube| cube.material.name == "leaves")
+            .filter(|cube| find_block(blocks, cube.position() - Vec3::new(0.0, cube_size, 0.0), cube_size).is_none())
+            .map(|cube| cube.position() - Vec3::new(0.0, cube_size / 2.0 + 0.02, 0.0))
+            .collect();
+
+        let surface = Vec3::new(cube_size * 0.4, 0.0, cube_size * 0.4);
+        ParticleSystem {
+            enabled: true,
+            emitters: vec![
+                Emitter::new(ParticleStyle::sparks(), magma.clone(), surface, 1),
+                Emitter::new(ParticleStyle::smoke(), magma, surface, 2),
+                Emitter::new(ParticleStyle::leaves(), leaves, surface, 3),
+            ],
+            debris: Emitter::new(ParticleStyle::debris(), Vec::new(), Vec3::repeat(cube_size * 0.5), 4),
+        }
+    }
+
+    // Mover los puntos de emisión a los bloques actuales (por ejemplo, después de editar).
+    // Las partículas vivas siguen su curso.
+    pub fn set_blocks(&mut self, blocks: &[Cube], cube_size: f32) {
+        let fresh = ParticleSystem::for_blocks(blocks, cube_size);
+        for (emitter, fresh) in self.emitters.iter_mut().zip(fresh.emitters) {
+            emitter.sources = fresh.sources;
+        }
+    }
+
+    // Apagar borra las partículas vivas; al encender vuelven a nacer desde cero
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.emitters.iter_mut().chain(std::iter::once(&mut self.debris)).for_each(|emitter| emitter.particles.clear());
+    }
+
+    // Ráfaga de escombros de una explosión en `center`
+    pub fn explode(&mut self, center: Vec3) {
+        if self.enabled {
+            self.debris.burst(center, self.debris.style.max_particles);
+        }
+    }
+
+    pub fn step(&mut self, dt: f32) {
+        if self.enabled {
+            self.emitters.iter_mut().chain(std::iter::once(&mut self.debris)).for_each(|emitter| emitter.step(dt));
+        }
+    }
+
+    // Partículas vivas en total
+    pub fn count(&self) -> usize {
+        if self.enabled {
+            self.emitters.iter().chain(std::iter::once(&self.debris)).map(|emitter| emitter.particles.len()).sum()
+        } else {
+            0
+        }
+    }
+
+    pub fn objects(&self, eye: &Vec3) -> Vec<Box<dyn RayIntersect>> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        self.emitters.iter().chain(std::iter::once(&self.debris)).flat_map(|emitter| emitter.objects(eye)).collect()
+    }
+}
+
+// Generador pseudoaleatorio (xorshift) con semilla fija, para que las secuencias sin ventana se repitan
+#[derive(Debug, Clone)]
+struct Rng {
+    state: u32,
+}
+
+impl Rng {
+    fn new(seed: u32) -> Self {
+        Rng { state: seed.wrapping_mul(0x9e37_79b9).max(1) }
+    }
+
+    fn next(&mut self) -> u32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        self.state
+    }
+
+    // En [0, 1)
+    fn unit(&mut self) -> f32 {
+        (self.next() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    // En [-1, 1)
+    fn signed(&mut self) -> f32 {
+        self.unit() * 2.0 - 1.0
+    }
+
+    // Entero en [0, n)
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() as usize) % n
+    }
+}
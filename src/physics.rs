@@ -0,0 +1,229 @@
+use nalgebra_glm::Vec3;
+use crate::color::Color;
+use crate::cube::Cube;
+use crate::editor::find_block;
+use crate::grid::{Grid3D, GridObject};
+use crate::light::Light;
+use crate::sky::mix;
+use std::sync::Arc;
+
+// Pasos del reloj entre actualizaciones de la gravedad (a 60 pasos por segundo, 20 celdas por segundo)
+const STEPS_PER_UPDATE: u32 = 3;
+// Celdas libres alrededor de los bloques; un bloque que cae por debajo del grid se pierde en el vacío
+const MARGIN: usize = 3;
+
+// Segundos entre encender la TNT y la explosión; la que enciende otra explosión dura menos
+const FUSE_TIME: f32 = 3.0;
+const CHAIN_FUSE_TIME: f32 = 0.5;
+// Radio de la explosión, en celdas
+const EXPLOSION_RADIUS: f32 = 1.5;
+// Duración del destello de la explosión, en segundos, y la intensidad de la luz al empezar
+const FLASH_TIME: f32 = 0.35;
+const FLASH_INTENSITY: f32 = 8.0;
+// Parpadeos por segundo de la TNT encendida
+const BLINK_RATE: f32 = 4.0;
+
+// TNT encendida, identificada por la posición de su bloque
+#[derive(Debug, Clone)]
+pub struct Fuse {
+    pub center: Vec3,
+    pub remaining: f32, // Segundos hasta la explosión
+}
+
+// Destello de una explosión reciente
+#[derive(Debug, Clone)]
+pub struct Flash {
+    pub center: Vec3,
+    pub age: f32,
+}
+
+// Lo que pasó en un paso de la física
+#[derive(Debug, Default)]
+pub struct PhysicsStep {
+    pub changed: bool,         // Algún bloque cayó o desapareció
+    pub explosions: Vec<Vec3>, // Centros de las explosiones de este paso
+    pub removed: Vec<usize>,   // Índices de los bloques quitados, en orden (cada uno sobre la lista de ese momento)
+}
+
+// Física de bloques sobre un Grid3D: los bloques de materiales que caen (arena) bajan mientras
+// tengan aire debajo, y la TNT encendida explota al terminar su mecha quitando los bloques cercanos.
+// Los bloques fuera del grid no se simulan.
+pub struct BlockPhysics {
+    pub grid: Grid3D,
+    pub fuses: Vec<Fuse>,
+    pub flashes: Vec<Flash>,
+    step_counter: u32,
+}
+
+impl BlockPhysics {
+    // Grid que cubre los bloques, alineado a sus centros
+    pub fn new(blocks: &[Cube], cell_size: f32) -> Self {
+        let mut min = blocks.iter().fold(Vec3::repeat(f32::INFINITY), |acc, cube| acc.inf(&cube.position()));
+        let mut max = blocks.iter().fold(Vec3::repeat(f32::NEG_INFINITY), |acc, cube| acc.sup(&cube.position()));
+        if min.x > max.x {
+            min = Vec3::zeros();
+            max = Vec3::zeros();
+        }
+        let extent = ((max - min) / cell_size).map(|c| c.round() as usize).max();
+        let size = extent + 1 + MARGIN * 2;
+        let origin = min - Vec3::repeat(MARGIN as f32 * cell_size);
+
+        BlockPhysics {
+            grid: Grid3D::placed(size, origin, cell_size),
+            fuses: Vec::new(),
+            flashes: Vec::new(),
+            step_counter: 0,
+        }
+    }
+
+    // Encender una TNT. Devuelve false si el bloque no es TNT o ya estaba encendido.
+    pub fn ignite(&mut self, block: &Cube) -> bool {
+        self.light_fuse(block, FUSE_TIME)
+    }
+
+    fn light_fuse(&mut self, block: &Cube, time: f32) -> bool {
+        let lit = self.fuses.iter().any(|fuse| (fuse.center - block.position()).magnitude() < block.size * 0.25);
+        if block.material.name != "tnt" || lit {
+            return false;
+        }
+        self.fuses.push(Fuse { center: block.position(), remaining: time });
+        true
+    }
+
+    // Hay mechas encendidas o destellos en curso
+    pub fn is_active(&self) -> bool {
+        !self.fuses.is_empty() || !self.flashes.is_empty()
+    }
+
+    // Un paso del reloj de `dt` segundos: mechas y destellos avanzan en cada paso, la gravedad
+    // solo cada STEPS_PER_UPDATE pasos
+    pub fn step(&mut self, blocks: &mut Vec<Cube>, dt: f32) -> PhysicsStep {
+        let mut result = PhysicsStep::default();
+
+        self.flashes.iter_mut().for_each(|flash| flash.age += dt);
+        self.flashes.retain(|flash| flash.age < FLASH_TIME);
+
+        // Una TNT que ya no está (la quitó el editor) no explota
+        let cell_size = self.grid.cell_size;
+        self.fuses.retain(|fuse| find_block(blocks, fuse.center, cell_size).is_some());
+        self.fuses.iter_mut().for_each(|fuse| fuse.remaining -= dt);
+        let (expired, lit): (Vec<Fuse>, Vec<Fuse>) = self.fuses.drain(..).partition(|fuse| fuse.remaining <= 0.0);
+        self.fuses = lit;
+        for fuse in expired {
+            self.explode(blocks, fuse.center, &mut result.removed);
+            result.explosions.push(fuse.center);
+            result.changed = true;
+        }
+
+        self.step_counter += 1;
+        if self.step_counter >= STEPS_PER_UPDATE {
+            self.step_counter = 0;
+            result.changed |= self.fall(blocks, &mut result.removed);
+        }
+        result
+    }
+
+    // Quitar los bloques dentro del radio; las otras TNT del radio se encienden con mecha corta
+    fn explode(&mut self, blocks: &mut Vec<Cube>, center: Vec3, removed: &mut Vec<usize>) {
+        let radius = EXPLOSION_RADIUS * self.grid.cell_size;
+        let epsilon = self.grid.cell_size * 0.25;
+        let mut chained = Vec::new();
+        let mut destroyed = Vec::new();
+        for (index, block) in blocks.iter().enumerate() {
+            let distance = (block.position() - center).magnitude();
+            if distance > radius + epsilon {
+                continue;
+            }
+            if block.material.name == "tnt" && distance > epsilon {
+                chained.push(block.clone());
+                continue;
+            }
+            destroyed.push(index);
+        }
+        // Del último al primero, así los índices que quedan no se corren
+        for index in destroyed.into_iter().rev() {
+            blocks.remove(index);
+            removed.push(index);
+        }
+        for block in &chained {
+            self.light_fuse(block, CHAIN_FUSE_TIME);
+        }
+        self.flashes.push(Flash { center, age: 0.0 });
+    }
+
+    // Bajar una celda cada bloque que cae y tiene aire debajo. Devuelve si alguno se movió.
+    fn fall(&mut self, blocks: &mut Vec<Cube>, removed: &mut Vec<usize>) -> bool {
+        let size = self.grid.size();
+        for x in 0..size {
+            for y in 0..size {
+                for z in 0..size {
+                    self.grid.place_object(x, y, z, GridObject::Empty);
+                }
+            }
+        }
+        for block in blocks.iter() {
+            if let Some((x, y, z)) = self.grid.cell_of(block.position()) {
+                self.grid.place_object(x, y, z, GridObject::Cube);
+            }
+        }
+
+        // De abajo hacia arriba, así una pila de arena cae junta
+        let mut falling: Vec<usize> = (0..blocks.len()).filter(|&index| blocks[index].material.falls).collect();
+        falling.sort_by(|&a, &b| blocks[a].center.y.total_cmp(&blocks[b].center.y));
+
+        let mut changed = false;
+        let mut lost = Vec::new();
+        for index in falling {
+            let (x, y, z) = match self.grid.cell_of(blocks[index].position()) {
+                Some(cell) => cell,
+                None => continue,
+            };
+            if self.grid.get(x as isize, y as isize - 1, z as isize) == Some(GridObject::Cube) {
+                continue;
+            }
+            self.grid.place_object(x, y, z, GridObject::Empty);
+            if y == 0 {
+                lost.push(index); // Cayó por debajo de la maqueta
+            } else {
+                self.grid.place_object(x, y - 1, z, GridObject::Cube);
+                blocks[index].center.y -= self.grid.cell_size;
+            }
+            changed = true;
+        }
+
+        lost.sort_unstable();
+        for index in lost.into_iter().rev() {
+            blocks.remove(index);
+            removed.push(index);
+        }
+        changed
+    }
+
+    // La TNT encendida parpadea en blanco
+    pub fn dress(&self, blocks: &mut [Cube]) {
+        for fuse in &self.fuses {
+            if (fuse.remaining * BLINK_RATE).fract() >= 0.5 {
+                continue;
+            }
+            if let Some(index) = find_block(blocks, fuse.center, self.grid.cell_size) {
+                let mut material = (*blocks[index].material).clone();
+                material.emission = 1.0;
+                blocks[index].material = Arc::new(material);
+            }
+        }
+    }
+
+    // Durante un destello, la explosión más reciente pasa a ser la fuente de luz y tiñe el ambiente
+    pub fn apply_flash(&self, light: &mut Light) {
+        let flash = match self.flashes.iter().min_by(|a, b| a.age.total_cmp(&b.age)) {
+            Some(flash) => flash,
+            None => return,
+        };
+        let strength = 1.0 - flash.age / FLASH_TIME;
+        let flash_color = Color::new(255, 210, 140);
+        light.position = flash.center + Vec3::new(0.0, self.grid.cell_size, 0.0);
+        light.color = mix(light.color, flash_color, strength);
+        light.intensity += (FLASH_INTENSITY - light.intensity) * strength;
+        light.sky.ambient = mix(light.sky.ambient, flash_color, strength * 0.5);
+    }
+}
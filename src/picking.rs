@@ -0,0 +1,50 @@
+use nalgebra_glm::Vec3;
+use crate::camera::{Camera, Eye, StereoMode};
+use crate::cast_ray::nearest_hit_in_range;
+use crate::material::Material;
+use crate::ray_intersect::RayIntersect;
+use crate::render::pixel_ray;
+use crate::stats;
+
+// Resultado de preguntar qué hay bajo un pixel
+#[derive(Debug, Clone)]
+pub struct Pick {
+    pub object_index: usize, // Índice en la lista de objetos consultada
+    pub point: Vec3,         // Punto de impacto en el mundo
+    pub normal: Vec3,        // Normal de la cara impactada
+    pub uv: (f32, f32),
+    pub distance: f32,
+    pub material: Material,
+}
+
+// Objeto bajo la posición `pixel` (en pixeles, desde la esquina superior izquierda) de una imagen
+// de tamaño `size`. Usa el mismo rayo primario y los mismos planos de recorte que render; en estéreo
+// lado a lado o arriba/abajo, el del ojo de la mitad de la imagen donde cae el pixel. No cuenta en
+// las estadísticas de rayos.
+pub fn pick(objects: &[Box<dyn RayIntersect>], camera: &Camera, pixel: (f32, f32), size: (f32, f32)) -> Option<Pick> {
+    let (x, y) = pixel;
+    let (width, height) = size;
+    let half_width = (width / 2.0).floor();
+    let half_height = (height / 2.0).floor();
+    let (view, pixel, size) = match camera.stereo {
+        StereoMode::SideBySide if x < half_width => (camera.eye_camera(Eye::Left), (x, y), (half_width, height)),
+        StereoMode::SideBySide => (camera.eye_camera(Eye::Right), (x - half_width, y), (half_width, height)),
+        StereoMode::OverUnder if y < half_height => (camera.eye_camera(Eye::Left), (x, y), (width, half_height)),
+        StereoMode::OverUnder => (camera.eye_camera(Eye::Right), (x, y - half_height), (width, half_height)),
+        StereoMode::Mono | StereoMode::Anaglyph => (camera.clone(), pixel, size),
+    };
+
+    let (origin, direction) = pixel_ray(&view, pixel, size)?;
+    let (object_index, hit) = stats::uncounted(|| {
+        nearest_hit_in_range(&origin, &direction, objects, view.clip_range(&direction))
+    });
+
+    Some(Pick {
+        object_index: object_index?,
+        point: hit.point,
+        normal: hit.normal,
+        uv: (hit.u, hit.v),
+        distance: hit.distance,
+        material: hit.material,
+    })
+}
@@ -0,0 +1,305 @@
+use nalgebra_glm::Vec3;
+use crate::framebuffer::{pack_pixel, unpack_pixel, Framebuffer};
+use std::fs;
+use std::path::Path;
+
+// Gamma aproximada del framebuffer; los efectos de luz trabajan en espacio lineal
+const GAMMA: f32 = 2.2;
+// Luminancia media que busca la exposición automática (gris medio)
+const AUTO_EXPOSURE_KEY: f32 = 0.18;
+// Velocidad de adaptación de la exposición automática, por segundo
+const AUTO_EXPOSURE_SPEED: f32 = 2.0;
+// Límites del factor de exposición automática
+const AUTO_EXPOSURE_RANGE: (f32, f32) = (0.25, 4.0);
+
+// Efectos de la cadena, en el orden en que se aplican
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    Exposure,     // Exposición manual en EV, más la automática si está activa
+    Bloom,        // Resplandor alrededor de las zonas más brillantes
+    Vignette,     // Oscurecimiento de los bordes
+    ColorGrading, // Contraste y saturación
+    Lut,          // Tabla de color 3D cargada de un archivo .cube
+}
+
+impl Effect {
+    pub const ALL: [Effect; 5] = [Effect::Exposure, Effect::Bloom, Effect::Vignette, Effect::ColorGrading, Effect::Lut];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Effect::Exposure => "exposure",
+            Effect::Bloom => "bloom",
+            Effect::Vignette => "vignette",
+            Effect::ColorGrading => "grade",
+            Effect::Lut => "lut",
+        }
+    }
+}
+
+// Cadena de posprocesado aplicada al framebuffer después de render
+pub struct PostProcess {
+    enabled: Vec<Effect>,
+    pub exposure_ev: f32,         // Compensación de exposición en pasos (2^ev)
+    auto_exposure: bool,          // La exposición se adapta a la luminancia media de la imagen
+    pub bloom_threshold: f32,     // Luminancia lineal a partir de la cual un pixel brilla
+    pub bloom_intensity: f32,
+    pub bloom_radius: usize,      // Radio del desenfoque, en pixeles
+    pub vignette_strength: f32,   // 0 = sin efecto, 1 = esquinas negras
+    pub contrast: f32,            // 1 = sin cambio
+    pub saturation: f32,          // 1 = sin cambio, 0 = blanco y negro
+    pub lut: Option<Lut>,
+    adapted_exposure: Option<f32>, // Factor de la exposición automática (None = aún sin medir)
+}
+
+impl PostProcess {
+    pub fn new() -> Self {
+        PostProcess {
+            enabled: Vec::new(),
+            exposure_ev: 0.0,
+            auto_exposure: true,
+            bloom_threshold: 0.75,
+            bloom_intensity: 0.6,
+            bloom_radius: 6,
+            vignette_strength: 0.45,
+            contrast: 1.15,
+            saturation: 1.2,
+            lut: None,
+            adapted_exposure: None,
+        }
+    }
+
+    pub fn auto_exposure(&self) -> bool {
+        self.auto_exposure
+    }
+
+    // Al volver a activarla, la exposición automática mide de nuevo en vez de partir del valor viejo
+    pub fn set_auto_exposure(&mut self, enabled: bool) {
+        if enabled && !self.auto_exposure {
+            self.adapted_exposure = None;
+        }
+        self.auto_exposure = enabled;
+    }
+
+    pub fn is_enabled(&self, effect: Effect) -> bool {
+        self.enabled.contains(&effect)
+    }
+
+    pub fn set_enabled(&mut self, effect: Effect, enabled: bool) {
+        self.enabled.retain(|&e| e != effect);
+        if enabled {
+            self.enabled.push(effect);
+        }
+    }
+
+    pub fn toggle(&mut self, effect: Effect) {
+        let enabled = !self.is_enabled(effect);
+        self.set_enabled(effect, enabled);
+    }
+
+    // Activar efectos a partir de una lista separada por comas (`bloom,vignette`)
+    pub fn enable_list(&mut self, list: &str) -> Result<(), String> {
+        for name in list.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            let effect = Effect::ALL.iter().find(|e| e.name() == name)
+                .ok_or_else(|| format!("efecto desconocido '{}'", name))?;
+            self.set_enabled(*effect, true);
+        }
+        Ok(())
+    }
+
+    // Aplicar los efectos activos. `delta_time` regula la adaptación de la exposición
+    // automática; el primer cuadro toma la exposición de inmediato.
+    pub fn apply(&mut self, framebuffer: &mut Framebuffer, delta_time: f32) {
+        if self.enabled.is_empty() {
+            return;
+        }
+        let (width, height) = (framebuffer.width, framebuffer.height);
+        let mut linear: Vec<Vec3> = framebuffer.buffer.iter()
+            .map(|&pixel| unpack_pixel(pixel).map(|c| c.powf(GAMMA)))
+            .collect();
+
+        if self.is_enabled(Effect::Exposure) {
+            let mut factor = 2.0f32.powf(self.exposure_ev);
+            if self.auto_exposure {
+                let target = (AUTO_EXPOSURE_KEY / average_luminance(&linear))
+                    .clamp(AUTO_EXPOSURE_RANGE.0, AUTO_EXPOSURE_RANGE.1);
+                let blend = 1.0 - (-delta_time * AUTO_EXPOSURE_SPEED).exp();
+                let adapted = self.adapted_exposure.map_or(target, |current| current + (target - current) * blend);
+                self.adapted_exposure = Some(adapted);
+                factor *= adapted;
+            }
+            linear.iter_mut().for_each(|c| *c *= factor);
+        }
+
+        if self.is_enabled(Effect::Bloom) {
+            // Lo que supera el umbral se desenfoca y se suma encima de la imagen
+            let bright: Vec<Vec3> = linear.iter().map(|c| {
+                let excess = luminance(c) - self.bloom_threshold;
+                if excess > 0.0 { c * (excess / luminance(c).max(1e-6)) } else { Vec3::zeros() }
+            }).collect();
+            let glow = gaussian_blur(&bright, width, height, self.bloom_radius);
+            for (c, g) in linear.iter_mut().zip(glow.iter()) {
+                *c += g * self.bloom_intensity;
+            }
+        }
+
+        if self.is_enabled(Effect::Vignette) {
+            for y in 0..height {
+                for x in 0..width {
+                    // Distancia al centro normalizada: 0 en el centro, 1 en las esquinas
+                    let dx = (x as f32 + 0.5) / width as f32 - 0.5;
+                    let dy = (y as f32 + 0.5) / height as f32 - 0.5;
+                    let distance = (dx * dx + dy * dy).sqrt() / 0.5f32.sqrt();
+                    let falloff = 1.0 - self.vignette_strength * distance.powi(2);
+                    linear[y * width + x] *= falloff.max(0.0);
+                }
+            }
+        }
+
+        // El resto trabaja sobre el color ya codificado para mostrar
+        let mut display: Vec<Vec3> = linear.iter()
+            .map(|c| c.map(|v| v.clamp(0.0, 1.0).powf(1.0 / GAMMA)))
+            .collect();
+
+        if self.is_enabled(Effect::ColorGrading) {
+            for c in display.iter_mut() {
+                let gray = luminance(c);
+                let saturated = Vec3::repeat(gray).lerp(c, self.saturation);
+                *c = (saturated - Vec3::repeat(0.5)) * self.contrast + Vec3::repeat(0.5);
+            }
+        }
+
+        if let Some(lut) = self.lut.as_ref().filter(|_| self.is_enabled(Effect::Lut)) {
+            display.iter_mut().for_each(|c| *c = lut.sample(c));
+        }
+
+        for (pixel, c) in framebuffer.buffer.iter_mut().zip(display.iter()) {
+            *pixel = pack_pixel(*c);
+        }
+    }
+}
+
+impl Default for PostProcess {
+    fn default() -> Self {
+        PostProcess::new()
+    }
+}
+
+// Tabla de color 3D en formato .cube (Adobe/Resolve)
+pub struct Lut {
+    pub size: usize,
+    pub domain_min: Vec3,
+    pub domain_max: Vec3,
+    table: Vec<Vec3>, // El índice rojo cambia más rápido, luego verde, luego azul
+}
+
+impl Lut {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let text = fs::read_to_string(path.as_ref())
+            .map_err(|e| format!("No se pudo leer {}: {}", path.as_ref().display(), e))?;
+        Lut::parse(&text).map_err(|e| format!("{}: {}", path.as_ref().display(), e))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut size = 0;
+        let mut domain_min = Vec3::zeros();
+        let mut domain_max = Vec3::repeat(1.0);
+        let mut table = Vec::new();
+
+        for (index, raw_line) in text.lines().enumerate() {
+            let line = raw_line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let error = |msg: &str| format!("Línea {}: {}", index + 1, msg);
+            let numbers = |values: &[&str]| -> Result<Vec3, String> {
+                let n: Result<Vec<f32>, _> = values.iter().map(|v| v.parse::<f32>()).collect();
+                match n.map_err(|_| error("número inválido"))?.as_slice() {
+                    [r, g, b] => Ok(Vec3::new(*r, *g, *b)),
+                    _ => Err(error("se esperaban 3 valores")),
+                }
+            };
+
+            match tokens[0] {
+                "TITLE" | "LUT_1D_INPUT_RANGE" | "LUT_3D_INPUT_RANGE" => {}
+                "LUT_1D_SIZE" => return Err(error("las tablas 1D no están soportadas")),
+                "LUT_3D_SIZE" => {
+                    size = tokens.get(1).and_then(|v| v.parse().ok()).ok_or_else(|| error("tamaño inválido"))?;
+                }
+                "DOMAIN_MIN" => domain_min = numbers(&tokens[1..])?,
+                "DOMAIN_MAX" => domain_max = numbers(&tokens[1..])?,
+                _ => table.push(numbers(&tokens)?),
+            }
+        }
+
+        if size < 2 {
+            return Err("falta LUT_3D_SIZE".to_string());
+        }
+        if table.len() != size * size * size {
+            return Err(format!("se esperaban {} entradas, hay {}", size * size * size, table.len()));
+        }
+        Ok(Lut { size, domain_min, domain_max, table })
+    }
+
+    // Color de salida con interpolación trilineal
+    pub fn sample(&self, color: &Vec3) -> Vec3 {
+        let n = self.size - 1;
+        let range = (self.domain_max - self.domain_min).map(|v| v.max(1e-6));
+        let coords = (color - self.domain_min).component_div(&range).map(|v| v.clamp(0.0, 1.0) * n as f32);
+
+        let base = coords.map(|v| (v.floor() as usize).min(n - 1));
+        let fraction = Vec3::new(coords.x - base.x as f32, coords.y - base.y as f32, coords.z - base.z as f32);
+        let at = |r: usize, g: usize, b: usize| self.table[(b * self.size + g) * self.size + r];
+
+        let mut result = Vec3::zeros();
+        for corner in 0..8 {
+            let (dr, dg, db) = (corner & 1, (corner >> 1) & 1, (corner >> 2) & 1);
+            let weight = (if dr == 1 { fraction.x } else { 1.0 - fraction.x })
+                * (if dg == 1 { fraction.y } else { 1.0 - fraction.y })
+                * (if db == 1 { fraction.z } else { 1.0 - fraction.z });
+            result += at(base.x + dr, base.y + dg, base.z + db) * weight;
+        }
+        result
+    }
+}
+
+fn luminance(color: &Vec3) -> f32 {
+    color.x * 0.2126 + color.y * 0.7152 + color.z * 0.0722
+}
+
+// Media geométrica de la luminancia (no se deja dominar por unos pocos pixeles muy brillantes)
+fn average_luminance(pixels: &[Vec3]) -> f32 {
+    if pixels.is_empty() {
+        return AUTO_EXPOSURE_KEY;
+    }
+    let log_sum: f32 = pixels.iter().map(|c| (luminance(c) + 1e-4).ln()).sum();
+    (log_sum / pixels.len() as f32).exp()
+}
+
+// Desenfoque gaussiano separable (horizontal y luego vertical)
+fn gaussian_blur(input: &[Vec3], width: usize, height: usize, radius: usize) -> Vec<Vec3> {
+    let sigma = (radius as f32 / 2.0).max(0.5);
+    let weights: Vec<f32> = (0..=radius).map(|i| (-((i * i) as f32) / (2.0 * sigma * sigma)).exp()).collect();
+    let total: f32 = weights[0] + 2.0 * weights[1..].iter().sum::<f32>();
+
+    let pass = |source: &[Vec3], horizontal: bool| -> Vec<Vec3> {
+        let mut output = vec![Vec3::zeros(); source.len()];
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = Vec3::zeros();
+                for offset in -(radius as isize)..=radius as isize {
+                    let (sx, sy) = if horizontal {
+                        ((x as isize + offset).clamp(0, width as isize - 1) as usize, y)
+                    } else {
+                        (x, (y as isize + offset).clamp(0, height as isize - 1) as usize)
+                    };
+                    sum += source[sy * width + sx] * weights[offset.unsigned_abs()];
+                }
+                output[y * width + x] = sum / total;
+            }
+        }
+        output
+    };
+
+    pass(&pass(input, true), false)
+}
@@ -58,6 +58,13 @@ where
 pub trait RayIntersect: CloneBox {
     fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect;
     fn position(&self) -> Vec3; // Añadir este método
+
+    // Impacto que cuenta a partir de la distancia `t_min` (el plano cercano): un objeto cerrado cuya
+    // entrada queda antes puede devolver su salida, así lo que corta el plano se ve por dentro.
+    // Por defecto es el impacto de siempre.
+    fn ray_intersect_from(&self, ray_origin: &Vec3, ray_direction: &Vec3, _t_min: f32) -> Intersect {
+        self.ray_intersect(ray_origin, ray_direction)
+    }
 }
 
 // Implementa Clone para Box<dyn RayIntersect> usando clone_box
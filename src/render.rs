@@ -1,28 +1,188 @@
 use crate::framebuffer::Framebuffer;
 use crate::light::Light;
-use crate::ray_intersect::RayIntersect; // Usamos RayIntersect en lugar de Sphere
-use crate::cast_ray::cast_ray;
-use crate::camera::Camera;
+use crate::ray_intersect::{Intersect, RayIntersect}; // Usamos RayIntersect en lugar de Sphere
+use crate::cast_ray::{nearest_hit_in_range, nearest_intersect_in_range, shade};
+use crate::camera::{Camera, Eye, StereoMode};
+use crate::color::Color;
+use crate::stats;
+use crate::debug_view::{debug_color, heat_color, DebugView};
 use nalgebra_glm::Vec3;
 
-pub fn render(framebuffer: &mut Framebuffer, objects: &[Box<dyn RayIntersect>], camera: &Camera, light: &Light) {
+// Ajustes de un render que no son de la cámara: qué se dibuja y el desplazamiento subpixel
+#[derive(Debug, Clone, Copy)]
+pub struct RenderSettings {
+    pub debug_view: DebugView, // Sombreado normal o visualización de depuración
+    pub jitter: (f32, f32),    // Desplazamiento subpixel de los rayos primarios, en pixeles
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings {
+            debug_view: DebugView::Shaded,
+            jitter: (0.0, 0.0),
+        }
+    }
+}
+
+// Muestras sobre el lente por pixel cuando la cámara tiene apertura
+const LENS_SAMPLES: usize = 8;
+const GOLDEN_ANGLE: f32 = 2.399_963;
+
+// Muestra `index` de un disco de Vogel (espiral de ángulo áureo) dentro del disco unitario
+fn lens_sample(index: usize, count: usize, rotation: f32) -> (f32, f32) {
+    let radius = ((index as f32 + 0.5) / count as f32).sqrt();
+    let theta = index as f32 * GOLDEN_ANGLE + rotation;
+    (radius * theta.cos(), radius * theta.sin())
+}
+
+// Rotación pseudoaleatoria por pixel para que el ruido del desenfoque no forme patrones
+fn pixel_rotation(x: usize, y: usize) -> f32 {
+    let mut h = (x as u32).wrapping_mul(0x8da6_b343) ^ (y as u32).wrapping_mul(0xd816_3841);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5bd1_e995);
+    h ^= h >> 15;
+    (h as f32 / u32::MAX as f32) * std::f32::consts::TAU
+}
+
+// Rayo primario: solo cuentan los impactos entre los planos de recorte de la cámara
+fn cast_primary_ray(ray_origin: &Vec3, ray_direction: &Vec3, objects: &[Box<dyn RayIntersect>], camera: &Camera, light: &Light) -> Color {
+    stats::count_primary_ray();
+    let intersect = nearest_intersect_in_range(ray_origin, ray_direction, objects, camera.clip_range(ray_direction));
+    shade(&intersect, ray_origin, ray_direction, objects, light, 0)
+}
+
+// `object_ids` da el identificador estable de cada objeto de `objects` (el índice del bloque en la
+// escena); los buffers auxiliares guardan ese identificador, y nada para los objetos sin uno (partículas)
+pub fn render(framebuffer: &mut Framebuffer, objects: &[Box<dyn RayIntersect>], object_ids: &[usize], camera: &Camera, light: &Light, settings: &RenderSettings) {
+    // Los buffers auxiliares solo se llenan en mono; en estéreo quedan vacíos
+    if let Some(aux) = framebuffer.aux.as_mut() {
+        aux.clear();
+    }
+
+    match camera.stereo {
+        StereoMode::Mono => render_view(framebuffer, objects, camera, light, settings),
+        StereoMode::Anaglyph => {
+            // Cada ojo se renderiza completo; el rojo sale del izquierdo y el verde/azul del derecho
+            let (left, right) = render_eyes(framebuffer.width, framebuffer.height, objects, camera, light, settings);
+            for (pixel, (l, r)) in framebuffer.buffer.iter_mut().zip(left.buffer.iter().zip(right.buffer.iter())) {
+                *pixel = (255 << 24) | (l & 0x00ff_0000) | (r & 0x0000_ffff);
+            }
+        }
+        StereoMode::SideBySide => {
+            let half_width = framebuffer.width / 2;
+            let (left, right) = render_eyes(half_width, framebuffer.height, objects, camera, light, settings);
+            framebuffer.blit(&left, 0, 0);
+            framebuffer.blit(&right, half_width, 0);
+        }
+        StereoMode::OverUnder => {
+            let half_height = framebuffer.height / 2;
+            let (left, right) = render_eyes(framebuffer.width, half_height, objects, camera, light, settings);
+            framebuffer.blit(&left, 0, 0);
+            framebuffer.blit(&right, 0, half_height);
+        }
+    }
+
+    if let Some(aux) = framebuffer.aux.as_mut() {
+        aux.map_object_ids(object_ids);
+    }
+}
+
+// Renderiza la vista de cada ojo en su propio framebuffer
+fn render_eyes(width: usize, height: usize, objects: &[Box<dyn RayIntersect>], camera: &Camera, light: &Light, settings: &RenderSettings) -> (Framebuffer, Framebuffer) {
+    let mut left = Framebuffer::new(width, height);
+    let mut right = Framebuffer::new(width, height);
+    render_view(&mut left, objects, &camera.eye_camera(Eye::Left), light, settings);
+    render_view(&mut right, objects, &camera.eye_camera(Eye::Right), light, settings);
+    (left, right)
+}
+
+// Renderiza una sola vista de la cámara sobre todo el framebuffer
+fn render_view(framebuffer: &mut Framebuffer, objects: &[Box<dyn RayIntersect>], camera: &Camera, light: &Light, settings: &RenderSettings) {
     let width = framebuffer.width as f32;
     let height = framebuffer.height as f32;
-    let aspect_ratio = width / height;
+
+    // El mapa de calor cuenta las pruebas de intersección de cada pixel y se colorea al final
+    let mut heat_counts = (settings.debug_view == DebugView::IntersectionHeatmap)
+        .then(|| vec![0u64; framebuffer.width * framebuffer.height]);
+    let shaded = matches!(settings.debug_view, DebugView::Shaded | DebugView::IntersectionHeatmap);
 
     for y in 0..framebuffer.height {
         for x in 0..framebuffer.width {
-            let screen_x = (2.0 * x as f32) / width - 1.0;
-            let screen_y = -(2.0 * y as f32) / height + 1.0;
-            let screen_x = screen_x * aspect_ratio;
+            let tests_before = stats::intersection_tests();
+            // Pixeles fuera de la proyección (p. ej. las esquinas del ojo de pez) quedan en negro
+            let pixel = (x as f32 + settings.jitter.0, y as f32 + settings.jitter.1);
+            let Some((ray_origin, ray_direction)) = pixel_ray(camera, pixel, (width, height)) else {
+                framebuffer.set_current_color(Color::black());
+                framebuffer.point(x as isize, y as isize);
+                continue;
+            };
 
-            let ray_direction = camera.basis_change(&Vec3::new(screen_x, screen_y, -1.0));
+            // El impacto del rayo central se usa para sombrear (sin apertura) y para los buffers auxiliares
+            let needs_hit = camera.aperture <= 0.0 || framebuffer.aux.is_some() || !shaded;
+            let (object_index, intersect) = if needs_hit {
+                stats::count_primary_ray();
+                nearest_hit_in_range(&ray_origin, &ray_direction, objects, camera.clip_range(&ray_direction))
+            } else {
+                (None, Intersect::empty())
+            };
+            if let Some(aux) = framebuffer.aux.as_mut() {
+                aux.record(y * framebuffer.width + x, object_index, &intersect);
+            }
 
             // Pasamos depth como argumento y usamos trait objects (RayIntersect)
-            let pixel_color = cast_ray(&camera.eye, &ray_direction, objects, light, 0);
+            let pixel_color = if !shaded {
+                debug_color(settings.debug_view, &intersect, objects, light)
+            } else if camera.aperture > 0.0 {
+                // Profundidad de campo: promediar varios rayos repartidos sobre el lente
+                // El desplazamiento subpixel también gira el patrón del lente entre cuadros acumulados
+                let rotation = pixel_rotation(x, y) + settings.jitter.0 * std::f32::consts::TAU;
+                let mut sum = [0.0f32; 3];
+                for i in 0..LENS_SAMPLES {
+                    let (lens_u, lens_v) = lens_sample(i, LENS_SAMPLES, rotation);
+                    let (origin, direction) = camera.lens_ray(&ray_origin, &ray_direction, lens_u, lens_v);
+                    let sample = cast_primary_ray(&origin, &direction, objects, camera, light);
+                    sum[0] += sample.r as f32;
+                    sum[1] += sample.g as f32;
+                    sum[2] += sample.b as f32;
+                }
+                let n = LENS_SAMPLES as f32;
+                Color::new((sum[0] / n) as u8, (sum[1] / n) as u8, (sum[2] / n) as u8)
+            } else {
+                shade(&intersect, &ray_origin, &ray_direction, objects, light, 0)
+            };
+
+            if let Some(counts) = heat_counts.as_mut() {
+                counts[y * framebuffer.width + x] = stats::intersection_tests() - tests_before;
+            }
 
             framebuffer.set_current_color(pixel_color);
             framebuffer.point(x as isize, y as isize);
         }
     }
+
+    if let Some(counts) = heat_counts {
+        let max = counts.iter().copied().max().unwrap_or(0).max(1) as f32;
+        for (index, &count) in counts.iter().enumerate() {
+            framebuffer.set_current_color(heat_color(count as f32 / max));
+            framebuffer.point((index % framebuffer.width) as isize, (index / framebuffer.width) as isize);
+        }
+    }
+}
+
+// Rayo primario que pasa por la posición `pixel` (en pixeles, desde la esquina superior
+// izquierda) de una imagen de tamaño `size`
+pub fn pixel_ray(camera: &Camera, pixel: (f32, f32), size: (f32, f32)) -> Option<(Vec3, Vec3)> {
+    let screen_x = (2.0 * pixel.0) / size.0 - 1.0;
+    let screen_y = -(2.0 * pixel.1) / size.1 + 1.0;
+    camera.primary_ray(screen_x, screen_y, size.0 / size.1)
+}
+
+// Intersección bajo el centro de la pantalla, usada para enfocar la cámara
+pub fn center_intersect(objects: &[Box<dyn RayIntersect>], camera: &Camera) -> Intersect {
+    match camera.primary_ray(0.0, 0.0, camera.aspect_ratio) {
+        Some((ray_origin, ray_direction)) => {
+            nearest_intersect_in_range(&ray_origin, &ray_direction, objects, camera.clip_range(&ray_direction))
+        }
+        None => Intersect::empty(),
+    }
 }
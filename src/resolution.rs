@@ -0,0 +1,116 @@
+use crate::framebuffer::Framebuffer;
+
+// Filtro usado al ampliar el render reducido al tamaño de la ventana
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpscaleFilter {
+    Nearest,  // Pixeles nítidos, estilo retro
+    Bilinear, // Mezcla de los 4 pixeles vecinos
+}
+
+// Ajusta la escala de render para acercarse a un tiempo de cuadro objetivo.
+// El tiempo de cuadro se suaviza con una media móvil exponencial y la escala
+// cambia en pasos pequeños, con una banda muerta para no oscilar.
+pub struct ResolutionController {
+    pub target_frame_time: f32,   // Segundos por cuadro deseados
+    pub smoothed_frame_time: f32, // Media móvil del tiempo de cuadro
+    pub scale: f32,               // Fracción del tamaño de la ventana que se renderiza
+    pub min_scale: f32,
+    pub max_scale: f32,
+    pub step: f32,                // Cambio de escala por ajuste
+    pub filter: UpscaleFilter,
+}
+
+// Peso del cuadro nuevo en la media móvil
+const SMOOTHING: f32 = 0.1;
+// Banda muerta alrededor del objetivo: solo se ajusta fuera de [objetivo * LOW, objetivo * HIGH]
+const DEADBAND_LOW: f32 = 0.8;
+const DEADBAND_HIGH: f32 = 1.1;
+
+impl ResolutionController {
+    pub fn new(target_fps: f32) -> Self {
+        let target_frame_time = 1.0 / target_fps.max(1.0);
+        ResolutionController {
+            target_frame_time,
+            smoothed_frame_time: target_frame_time,
+            scale: 1.0,
+            min_scale: 0.25,
+            max_scale: 1.0,
+            step: 0.05,
+            filter: UpscaleFilter::Bilinear,
+        }
+    }
+
+    // Registrar el tiempo del último cuadro y ajustar la escala
+    pub fn update(&mut self, frame_time: f32) {
+        self.smoothed_frame_time += (frame_time - self.smoothed_frame_time) * SMOOTHING;
+
+        if self.smoothed_frame_time > self.target_frame_time * DEADBAND_HIGH {
+            self.scale -= self.step;
+        } else if self.smoothed_frame_time < self.target_frame_time * DEADBAND_LOW {
+            self.scale += self.step;
+        }
+        self.scale = self.scale.clamp(self.min_scale, self.max_scale);
+    }
+
+    // Tamaño del render para una ventana de `width` x `height`
+    pub fn render_size(&self, width: usize, height: usize) -> (usize, usize) {
+        (
+            ((width as f32 * self.scale).round() as usize).max(1),
+            ((height as f32 * self.scale).round() as usize).max(1),
+        )
+    }
+
+    pub fn toggle_filter(&mut self) {
+        self.filter = match self.filter {
+            UpscaleFilter::Nearest => UpscaleFilter::Bilinear,
+            UpscaleFilter::Bilinear => UpscaleFilter::Nearest,
+        };
+    }
+}
+
+// Ampliar (o reducir) `source` para llenar todo `target`
+pub fn upscale(source: &Framebuffer, target: &mut Framebuffer, filter: UpscaleFilter) {
+    if source.width == target.width && source.height == target.height {
+        target.buffer.copy_from_slice(&source.buffer);
+        return;
+    }
+
+    let scale_x = source.width as f32 / target.width as f32;
+    let scale_y = source.height as f32 / target.height as f32;
+
+    for y in 0..target.height {
+        // Centro del pixel destino en coordenadas de la fuente
+        let sy = ((y as f32 + 0.5) * scale_y - 0.5).max(0.0);
+        for x in 0..target.width {
+            let sx = ((x as f32 + 0.5) * scale_x - 0.5).max(0.0);
+            target.buffer[y * target.width + x] = match filter {
+                UpscaleFilter::Nearest => {
+                    let px = (sx.round() as usize).min(source.width - 1);
+                    let py = (sy.round() as usize).min(source.height - 1);
+                    source.buffer[py * source.width + px]
+                }
+                UpscaleFilter::Bilinear => bilinear(source, sx, sy),
+            };
+        }
+    }
+}
+
+fn bilinear(source: &Framebuffer, x: f32, y: f32) -> u32 {
+    let x0 = (x as usize).min(source.width - 1);
+    let y0 = (y as usize).min(source.height - 1);
+    let x1 = (x0 + 1).min(source.width - 1);
+    let y1 = (y0 + 1).min(source.height - 1);
+    let fx = x - x0 as f32;
+    let fy = y - y0 as f32;
+
+    let pixel = |px: usize, py: usize| source.buffer[py * source.width + px];
+    let (p00, p10, p01, p11) = (pixel(x0, y0), pixel(x1, y0), pixel(x0, y1), pixel(x1, y1));
+
+    let channel = |shift: u32| -> u32 {
+        let c = |p: u32| ((p >> shift) & 0xff) as f32;
+        let top = c(p00) + (c(p10) - c(p00)) * fx;
+        let bottom = c(p01) + (c(p11) - c(p01)) * fx;
+        ((top + (bottom - top) * fy).round() as u32).min(255)
+    };
+    (255 << 24) | (channel(16) << 16) | (channel(8) << 8) | channel(0)
+}
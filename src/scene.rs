@@ -0,0 +1,303 @@
+use nalgebra_glm::Vec3;
+use crate::animation::{Channel, Curve, Easing, Target, Track};
+use crate::camera::{Camera, Projection, StereoMode, ViewPreset};
+use crate::cube::Cube;
+use std::fs;
+use std::path::Path;
+
+// Ajustes de cámara leídos de un archivo de escena. Los campos ausentes conservan
+// el valor que ya tenga la cámara.
+#[derive(Debug, Clone, Default)]
+pub struct CameraSettings {
+    pub eye: Option<Vec3>,
+    pub center: Option<Vec3>,
+    pub up: Option<Vec3>,
+    pub fov: Option<f32>,
+    pub near_plane: Option<f32>,
+    pub far_plane: Option<f32>,
+    pub aperture: Option<f32>,
+    pub focus_distance: Option<f32>,
+    pub projection: Option<Projection>,
+    pub ortho_height: Option<f32>,
+    pub fisheye_fov: Option<f32>,
+    pub preset: Option<ViewPreset>,
+    pub stereo: Option<StereoMode>,
+    pub interocular_distance: Option<f32>,
+    pub convergence_distance: Option<f32>,
+}
+
+impl CameraSettings {
+    // Aplicar los ajustes presentes sobre una cámara existente
+    pub fn apply(&self, camera: &mut Camera) {
+        if let Some(eye) = self.eye { camera.eye = eye; }
+        if let Some(center) = self.center { camera.center = center; }
+        if let Some(up) = self.up { camera.up = up; }
+        if let Some(fov) = self.fov { camera.fov = fov; }
+        if let Some(near_plane) = self.near_plane { camera.near_plane = near_plane; }
+        if let Some(far_plane) = self.far_plane { camera.far_plane = far_plane; }
+        if let Some(aperture) = self.aperture { camera.aperture = aperture; }
+        if let Some(focus_distance) = self.focus_distance { camera.focus_distance = focus_distance; }
+        if let Some(ortho_height) = self.ortho_height { camera.ortho_height = ortho_height; }
+        if let Some(fisheye_fov) = self.fisheye_fov { camera.fisheye_fov = fisheye_fov; }
+        if let Some(stereo) = self.stereo { camera.stereo = stereo; }
+        if let Some(distance) = self.interocular_distance { camera.interocular_distance = distance; }
+        if let Some(distance) = self.convergence_distance { camera.convergence_distance = distance; }
+
+        // El preset recoloca el ojo; una proyección explícita tiene la última palabra
+        if let Some(preset) = self.preset { camera.apply_preset(preset); }
+        if let Some(projection) = self.projection { camera.projection = projection; }
+    }
+}
+
+// Un bloque de la maqueta: centro en el mundo y nombre del material
+#[derive(Debug, Clone)]
+pub struct BlockSpec {
+    pub position: Vec3,
+    pub material: String,
+}
+
+// Contenido de un archivo de escena
+#[derive(Debug, Clone, Default)]
+pub struct SceneDescription {
+    pub camera: CameraSettings,
+    pub blocks: Vec<BlockSpec>, // Si hay bloques, reemplazan a la maqueta incorporada
+    pub tracks: Vec<Track>,     // Si hay pistas, reemplazan a la animación incorporada del agua
+}
+
+// Cargar una escena desde un archivo de texto
+pub fn load_scene<P: AsRef<Path>>(path: P) -> Result<SceneDescription, String> {
+    let text = fs::read_to_string(path.as_ref())
+        .map_err(|e| format!("No se pudo leer {}: {}", path.as_ref().display(), e))?;
+    parse_scene(&text)
+}
+
+// Formato por líneas; `#` inicia un comentario:
+//
+//   camera eye 0 2.5 5
+//   camera center 0 0 0
+//   camera fov 60
+//   camera projection orthographic
+//   camera ortho_height 6
+//   camera preset isometric
+//   block 0 0.5 -1 stone
+//   animate water y sine 0.05 7 0 0.16
+//   animate @12 rotation keys ease_in_out 0:0 2:90 4:0 loop
+pub fn parse_scene(text: &str) -> Result<SceneDescription, String> {
+    let mut scene = SceneDescription::default();
+
+    for (index, raw_line) in text.lines().enumerate() {
+        let line = raw_line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let result = match tokens[0] {
+            "camera" => parse_camera_line(&tokens[1..], &mut scene.camera),
+            "block" => parse_block_line(&tokens[1..]).map(|block| scene.blocks.push(block)),
+            "animate" => parse_animate_line(&tokens[1..]).map(|track| scene.tracks.push(track)),
+            other => Err(format!("sección desconocida '{}'", other)),
+        };
+
+        result.map_err(|e| format!("Línea {}: {}", index + 1, e))?;
+    }
+
+    Ok(scene)
+}
+
+fn parse_camera_line(tokens: &[&str], settings: &mut CameraSettings) -> Result<(), String> {
+    let (key, values) = tokens.split_first().ok_or("falta el ajuste de cámara")?;
+
+    match *key {
+        "eye" => settings.eye = Some(parse_vec3(values)?),
+        "center" => settings.center = Some(parse_vec3(values)?),
+        "up" => settings.up = Some(parse_vec3(values)?),
+        "fov" => settings.fov = Some(parse_f32(values)?),
+        "near" => settings.near_plane = Some(parse_f32(values)?),
+        "far" => settings.far_plane = Some(parse_f32(values)?),
+        "aperture" => settings.aperture = Some(parse_f32(values)?),
+        "focus" => settings.focus_distance = Some(parse_f32(values)?),
+        "ortho_height" => settings.ortho_height = Some(parse_f32(values)?),
+        "fisheye_fov" => settings.fisheye_fov = Some(parse_f32(values)?),
+        "projection" => settings.projection = Some(parse_projection(values)?),
+        "preset" => settings.preset = Some(parse_preset(values)?),
+        "stereo" => settings.stereo = Some(parse_stereo(values)?),
+        "interocular" => settings.interocular_distance = Some(parse_f32(values)?),
+        "convergence" => settings.convergence_distance = Some(parse_f32(values)?),
+        other => return Err(format!("ajuste de cámara desconocido '{}'", other)),
+    }
+
+    Ok(())
+}
+
+fn parse_block_line(tokens: &[&str]) -> Result<BlockSpec, String> {
+    match tokens {
+        [_, _, _, material] => Ok(BlockSpec {
+            position: parse_vec3(&tokens[..3])?,
+            material: material.to_string(),
+        }),
+        _ => Err("se esperaba 'block x y z material'".to_string()),
+    }
+}
+
+// Pista de animación:
+//   animate <objetivo> <canal> sine <amplitud> <periodo> [fase] [desfase]
+//   animate <objetivo> <canal> keys <easing> <tiempo>:<valor>... [loop]
+// El objetivo es `@<índice>` para un bloque o el nombre de un material para todos sus bloques.
+fn parse_animate_line(tokens: &[&str]) -> Result<Track, String> {
+    let [target, channel, kind, values @ ..] = tokens else {
+        return Err("se esperaba 'animate objetivo canal sine|keys ...'".to_string());
+    };
+    let target = match target.strip_prefix('@') {
+        Some(index) => Target::Block(index.parse().map_err(|_| format!("bloque inválido '{}'", target))?),
+        None => Target::Material(target.to_string()),
+    };
+    let channel = Channel::from_name(channel).ok_or_else(|| format!("canal desconocido '{}'", channel))?;
+
+    let curve = match *kind {
+        "sine" => {
+            if values.len() < 2 || values.len() > 4 {
+                return Err("se esperaba 'sine amplitud periodo [fase] [desfase]'".to_string());
+            }
+            let number = |i: usize| values.get(i).map_or(Ok(0.0), |v| parse_f32(&[v]));
+            let period = number(1)?;
+            if period <= 0.0 {
+                return Err("el periodo debe ser positivo".to_string());
+            }
+            Curve::Sine { amplitude: number(0)?, period, phase: number(2)?, stagger: number(3)? }
+        }
+        "keys" => {
+            let (easing, mut keyframes) = values.split_first().ok_or("falta el easing")?;
+            let easing = Easing::from_name(easing).ok_or_else(|| format!("easing desconocido '{}'", easing))?;
+            let looping = keyframes.last() == Some(&"loop");
+            if looping {
+                keyframes = &keyframes[..keyframes.len() - 1];
+            }
+            let mut keys = Vec::new();
+            for keyframe in keyframes {
+                let (time, value) = keyframe.split_once(':')
+                    .ok_or_else(|| format!("keyframe inválido '{}' (tiempo:valor)", keyframe))?;
+                let key = (parse_f32(&[time])?, parse_f32(&[value])?);
+                if keys.last().is_some_and(|last: &(f32, f32)| last.0 >= key.0) {
+                    return Err("los keyframes deben ir en orden de tiempo".to_string());
+                }
+                keys.push(key);
+            }
+            if keys.is_empty() {
+                return Err("faltan keyframes".to_string());
+            }
+            Curve::Keyframes { keys, easing, looping }
+        }
+        other => return Err(format!("curva desconocida '{}' (sine o keys)", other)),
+    };
+
+    Ok(Track { target, channel, curve })
+}
+
+// Línea `animate` que vuelve a leerse como la misma pista
+fn format_track(track: &Track) -> String {
+    let target = match &track.target {
+        Target::Block(index) => format!("@{}", index),
+        Target::Material(name) => name.clone(),
+    };
+    let curve = match &track.curve {
+        Curve::Sine { amplitude, period, phase, stagger } => format!("sine {} {} {} {}", amplitude, period, phase, stagger),
+        Curve::Keyframes { keys, easing, looping } => {
+            let keys: Vec<String> = keys.iter().map(|(time, value)| format!("{}:{}", time, value)).collect();
+            format!("keys {} {}{}", easing.name(), keys.join(" "), if *looping { " loop" } else { "" })
+        }
+    };
+    format!("animate {} {} {}\n", target, track.channel.name(), curve)
+}
+
+// Guardar la pose de la cámara, los bloques y las pistas en el mismo formato que lee parse_scene
+pub fn save_scene<P: AsRef<Path>>(path: P, camera: &Camera, blocks: &[Cube], tracks: &[Track]) -> Result<(), String> {
+    let mut text = String::from("# Escena: camera <ajuste> <valores> / block <x y z> <material> / animate <objetivo> <canal> <curva>\n");
+    text.push_str(&format!("camera eye {} {} {}\n", camera.eye.x, camera.eye.y, camera.eye.z));
+    text.push_str(&format!("camera center {} {} {}\n", camera.center.x, camera.center.y, camera.center.z));
+    text.push_str(&format!("camera up {} {} {}\n", camera.up.x, camera.up.y, camera.up.z));
+    text.push_str(&format!("camera fov {}\n", camera.fov));
+    // Todos los ajustes que lee parse_camera_line, para que la escena vuelva a verse igual
+    text.push_str(&format!("camera near {}\n", camera.near_plane));
+    text.push_str(&format!("camera far {}\n", camera.far_plane));
+    text.push_str(&format!("camera aperture {}\n", camera.aperture));
+    text.push_str(&format!("camera focus {}\n", camera.focus_distance));
+    text.push_str(&format!("camera ortho_height {}\n", camera.ortho_height));
+    text.push_str(&format!("camera fisheye_fov {}\n", camera.fisheye_fov));
+    text.push_str(&format!("camera projection {}\n", projection_name(camera.projection)));
+    text.push_str(&format!("camera stereo {}\n", stereo_name(camera.stereo)));
+    text.push_str(&format!("camera interocular {}\n", camera.interocular_distance));
+    text.push_str(&format!("camera convergence {}\n", camera.convergence_distance));
+    for block in blocks {
+        let p = block.position();
+        text.push_str(&format!("block {} {} {} {}\n", p.x, p.y, p.z, block.material.name));
+    }
+    for track in tracks {
+        text.push_str(&format_track(track));
+    }
+    fs::write(path.as_ref(), text)
+        .map_err(|e| format!("No se pudo guardar {}: {}", path.as_ref().display(), e))
+}
+
+fn parse_f32(values: &[&str]) -> Result<f32, String> {
+    match values {
+        [value] => value.parse().map_err(|_| format!("número inválido '{}'", value)),
+        _ => Err(format!("se esperaba 1 valor, hay {}", values.len())),
+    }
+}
+
+fn parse_vec3(values: &[&str]) -> Result<Vec3, String> {
+    if values.len() != 3 {
+        return Err(format!("se esperaban 3 valores, hay {}", values.len()));
+    }
+    let x = parse_f32(&values[0..1])?;
+    let y = parse_f32(&values[1..2])?;
+    let z = parse_f32(&values[2..3])?;
+    Ok(Vec3::new(x, y, z))
+}
+
+pub fn parse_projection(values: &[&str]) -> Result<Projection, String> {
+    match values {
+        ["perspective"] => Ok(Projection::Perspective),
+        ["orthographic"] => Ok(Projection::Orthographic),
+        ["equirectangular"] => Ok(Projection::Equirectangular),
+        ["fisheye"] => Ok(Projection::Fisheye),
+        _ => Err(format!("proyección desconocida '{}'", values.join(" "))),
+    }
+}
+
+fn projection_name(projection: Projection) -> &'static str {
+    match projection {
+        Projection::Perspective => "perspective",
+        Projection::Orthographic => "orthographic",
+        Projection::Equirectangular => "equirectangular",
+        Projection::Fisheye => "fisheye",
+    }
+}
+
+fn parse_preset(values: &[&str]) -> Result<ViewPreset, String> {
+    match values {
+        ["isometric"] => Ok(ViewPreset::Isometric),
+        ["dimetric"] => Ok(ViewPreset::Dimetric),
+        _ => Err(format!("preset desconocido '{}'", values.join(" "))),
+    }
+}
+
+pub fn parse_stereo(values: &[&str]) -> Result<StereoMode, String> {
+    match values {
+        ["mono"] => Ok(StereoMode::Mono),
+        ["anaglyph"] => Ok(StereoMode::Anaglyph),
+        ["side_by_side"] => Ok(StereoMode::SideBySide),
+        ["over_under"] => Ok(StereoMode::OverUnder),
+        _ => Err(format!("modo estéreo desconocido '{}'", values.join(" "))),
+    }
+}
+
+fn stereo_name(stereo: StereoMode) -> &'static str {
+    match stereo {
+        StereoMode::Mono => "mono",
+        StereoMode::Anaglyph => "anaglyph",
+        StereoMode::SideBySide => "side_by_side",
+        StereoMode::OverUnder => "over_under",
+    }
+}
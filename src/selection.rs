@@ -0,0 +1,93 @@
+use nalgebra_glm::Vec3;
+use crate::framebuffer::{pack_pixel, unpack_pixel, AuxBuffers, Framebuffer, NO_HIT};
+
+// Color del contorno y del tinte de los bloques seleccionados
+const OUTLINE_COLOR: [f32; 3] = [1.0, 0.85, 0.1];
+const TINT_STRENGTH: f32 = 0.25;
+// Dos normales vecinas con un coseno menor a este están en caras distintas (arista del cubo)
+const EDGE_COS: f32 = 0.9;
+
+// Bloques seleccionados, por su identificador estable
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    pub ids: Vec<usize>,
+}
+
+impl Selection {
+    pub fn new() -> Self {
+        Selection::default()
+    }
+
+    pub fn contains(&self, id: usize) -> bool {
+        self.ids.contains(&id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.ids.clear();
+    }
+
+    // Dejar solo `id` seleccionado
+    pub fn set(&mut self, id: usize) {
+        self.ids.clear();
+        self.ids.push(id);
+    }
+
+    // Agregar o quitar `id` de la selección múltiple
+    pub fn toggle(&mut self, id: usize) {
+        match self.ids.iter().position(|&selected| selected == id) {
+            Some(index) => {
+                self.ids.remove(index);
+            }
+            None => self.ids.push(id),
+        }
+    }
+
+    // Pasada superpuesta sobre la imagen: tiñe los bloques seleccionados y marca su contorno
+    // y sus aristas visibles. Se guía por los IDs y normales de los buffers auxiliares `aux` del
+    // mismo tamaño que la imagen (en estéreo quedan vacíos y no se marca nada).
+    pub fn draw_outline(&self, framebuffer: &mut Framebuffer, aux: &AuxBuffers) {
+        let (width, height) = (framebuffer.width, framebuffer.height);
+        if self.is_empty() || aux.object_id.len() != width * height {
+            return;
+        }
+        let stable_id = |index: usize| -> Option<usize> {
+            match aux.object_id[index] {
+                NO_HIT => None,
+                id => Some(id as usize),
+            }
+        };
+
+        for y in 0..height {
+            for x in 0..width {
+                let index = y * width + x;
+                let Some(id) = stable_id(index).filter(|&id| self.contains(id)) else {
+                    continue;
+                };
+
+                // Un pixel es de borde si algún vecino es de otro objeto o de otra cara del mismo cubo
+                let neighbours = [
+                    (x > 0).then(|| index - 1),
+                    (x + 1 < width).then(|| index + 1),
+                    (y > 0).then(|| index - width),
+                    (y + 1 < height).then(|| index + width),
+                ];
+                let edge = neighbours.iter().any(|neighbour| match neighbour {
+                    Some(n) => stable_id(*n) != Some(id) || aux.normal[*n].dot(&aux.normal[index]) < EDGE_COS,
+                    None => true,
+                });
+
+                let outline = Vec3::from(OUTLINE_COLOR);
+                let color = if edge {
+                    outline
+                } else {
+                    unpack_pixel(framebuffer.buffer[index]).lerp(&outline, TINT_STRENGTH)
+                };
+                framebuffer.buffer[index] = pack_pixel(color);
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use nalgebra_glm::Vec3;
+use crate::color::Color;
+
+// Celdas por unidad de dirección del campo de estrellas y fracción de celdas con estrella
+const STAR_DENSITY: f32 = 180.0;
+const STAR_CHANCE: u32 = 3; // Por cada 1000 celdas
+// Coseno del radio angular de los discos del sol y la luna
+const SUN_DISK_COS: f32 = 0.9995;
+const MOON_DISK_COS: f32 = 0.9993;
+
+// Fondo de la escena y luz ambiental que lo acompaña
+#[derive(Debug, Clone)]
+pub struct Sky {
+    pub zenith: Color,  // Color mirando hacia arriba
+    pub horizon: Color, // Color en el horizonte y por debajo
+    pub ambient: Color, // Luz ambiental que reciben todas las superficies
+    pub stars: f32,     // Brillo del campo de estrellas en [0, 1]; 0 de día
+    pub sun_direction: Option<Vec3>, // Hacia el sol, para dibujar su disco y el de la luna (opuesta)
+}
+
+impl Sky {
+    // Cielo de un solo color, sin estrellas ni discos
+    pub fn flat(color: Color, ambient: Color) -> Self {
+        Sky {
+            zenith: color,
+            horizon: color,
+            ambient,
+            stars: 0.0,
+            sun_direction: None,
+        }
+    }
+
+    // Color del cielo visto en la dirección `direction`
+    pub fn color(&self, direction: &Vec3) -> Color {
+        let direction = direction.normalize();
+        let mut color = mix(self.horizon, self.zenith, direction.y.clamp(0.0, 1.0).sqrt());
+
+        if let Some(sun) = self.sun_direction {
+            let alignment = direction.dot(&sun);
+            if alignment > SUN_DISK_COS {
+                return Color::new(255, 244, 214);
+            }
+            if -alignment > MOON_DISK_COS {
+                return mix(color, Color::new(225, 230, 240), self.stars.max(0.3));
+            }
+        }
+
+        if self.stars > 0.0 && direction.y > 0.0 {
+            let brightness = star(&direction) * self.stars;
+            color = mix(color, Color::new(255, 255, 255), brightness);
+        }
+        color
+    }
+}
+
+// Brillo de la estrella en la celda de esta dirección (0 si la celda no tiene estrella)
+fn star(direction: &Vec3) -> f32 {
+    let cell = direction.map(|c| (c * STAR_DENSITY).floor() as i32);
+    let mut hash = (cell.x as u32).wrapping_mul(73_856_093)
+        ^ (cell.y as u32).wrapping_mul(19_349_663)
+        ^ (cell.z as u32).wrapping_mul(83_492_791);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0x5bd1_e995);
+    hash ^= hash >> 15;
+
+    if hash % 1000 < STAR_CHANCE {
+        0.4 + ((hash >> 10) % 60) as f32 / 100.0
+    } else {
+        0.0
+    }
+}
+
+// Interpolación lineal entre dos colores, con `t` en [0, 1]
+pub fn mix(a: Color, b: Color, t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    let channel = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * t).round() as u8;
+    Color::new(channel(a.r, b.r), channel(a.g, b.g), channel(a.b, b.b))
+}
@@ -0,0 +1,52 @@
+use nalgebra_glm::Vec3;
+use crate::ray_intersect::{RayIntersect, Intersect};
+use crate::material::Material;
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct Sphere {
+    pub center: Vec3,
+    pub radius: f32,
+    pub material: Arc<Material>,
+}
+
+impl Sphere {
+    pub fn new(center: Vec3, radius: f32, material: Arc<Material>) -> Self {
+        Sphere { center, radius, material }
+    }
+}
+
+impl RayIntersect for Sphere {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        // Resolver |o + t d - c|² = r² para t
+        let oc = ray_origin - self.center;
+        let a = ray_direction.dot(ray_direction);
+        let b = 2.0 * oc.dot(ray_direction);
+        let c = oc.dot(&oc) - self.radius * self.radius;
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return Intersect::empty();
+        }
+
+        // La raíz más cercana delante del origen; si el origen está dentro, la de salida
+        let root = discriminant.sqrt();
+        let near = (-b - root) / (2.0 * a);
+        let far = (-b + root) / (2.0 * a);
+        let distance = if near > 0.0 { near } else { far };
+        if distance <= 0.0 {
+            return Intersect::empty();
+        }
+
+        let point = ray_origin + ray_direction * distance;
+        let normal = (point - self.center).normalize();
+        // Coordenadas esféricas como UV
+        let u = 0.5 + normal.z.atan2(normal.x) / (2.0 * PI);
+        let v = 0.5 - normal.y.asin() / PI;
+        Intersect::new(point, normal, distance, (*self.material).clone(), u, v)
+    }
+
+    fn position(&self) -> Vec3 {
+        self.center
+    }
+}
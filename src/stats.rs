@@ -0,0 +1,64 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Contadores globales de trabajo de trazado; se leen y se ponen en cero una vez por cuadro
+static PRIMARY_RAYS: AtomicU64 = AtomicU64::new(0);
+static SECONDARY_RAYS: AtomicU64 = AtomicU64::new(0);
+static SHADOW_RAYS: AtomicU64 = AtomicU64::new(0);
+static INTERSECTION_TESTS: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RayStats {
+    pub primary_rays: u64,        // Rayos desde la cámara (incluye las muestras del lente)
+    pub secondary_rays: u64,      // Reflexiones y refracciones
+    pub shadow_rays: u64,
+    pub intersection_tests: u64,  // Pruebas rayo-objeto
+}
+
+impl RayStats {
+    pub fn total_rays(&self) -> u64 {
+        self.primary_rays + self.secondary_rays + self.shadow_rays
+    }
+}
+
+pub fn count_primary_ray() {
+    PRIMARY_RAYS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn count_secondary_ray() {
+    SECONDARY_RAYS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn count_shadow_ray() {
+    SHADOW_RAYS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn count_intersection_tests(tests: usize) {
+    INTERSECTION_TESTS.fetch_add(tests as u64, Ordering::Relaxed);
+}
+
+// Pruebas rayo-objeto hechas hasta ahora, sin poner el contador en cero
+pub fn intersection_tests() -> u64 {
+    INTERSECTION_TESTS.load(Ordering::Relaxed)
+}
+
+// Ejecutar `f` sin que sus rayos y pruebas cuenten en las estadísticas (consultas fuera del render,
+// como el picking, que si no aparecerían en el cuadro siguiente)
+pub fn uncounted<T>(f: impl FnOnce() -> T) -> T {
+    let counters = [&PRIMARY_RAYS, &SECONDARY_RAYS, &SHADOW_RAYS, &INTERSECTION_TESTS];
+    let before = counters.map(|counter| counter.load(Ordering::Relaxed));
+    let result = f();
+    for (counter, value) in counters.iter().zip(before) {
+        counter.store(value, Ordering::Relaxed);
+    }
+    result
+}
+
+// Leer los contadores acumulados desde la última llamada y ponerlos en cero
+pub fn take() -> RayStats {
+    RayStats {
+        primary_rays: PRIMARY_RAYS.swap(0, Ordering::Relaxed),
+        secondary_rays: SECONDARY_RAYS.swap(0, Ordering::Relaxed),
+        shadow_rays: SHADOW_RAYS.swap(0, Ordering::Relaxed),
+        intersection_tests: INTERSECTION_TESTS.swap(0, Ordering::Relaxed),
+    }
+}
@@ -0,0 +1,81 @@
+use nalgebra_glm::Vec3;
+use crate::camera::Camera;
+use crate::light::Light;
+use crate::ray_intersect::RayIntersect;
+
+// Vuelta completa de la cámara alrededor de la maqueta, a elevación fija
+pub struct Turntable {
+    pub frames: usize,      // Cuadros por vuelta
+    pub elevation: f32,     // Elevación de la cámara sobre el plano XZ, en grados
+    pub rotate_light: bool, // Si la luz gira junto con la cámara
+}
+
+impl Turntable {
+    pub fn new(frames: usize, elevation: f32, rotate_light: bool) -> Self {
+        Turntable {
+            frames: frames.max(1),
+            elevation,
+            rotate_light,
+        }
+    }
+
+    // Ángulo que avanza la cámara entre cuadros
+    pub fn step_angle(&self) -> f32 {
+        std::f32::consts::TAU / self.frames as f32
+    }
+
+    // Apuntar la cámara al centro de la escena y fijar la elevación, conservando la distancia
+    pub fn setup(&self, camera: &mut Camera, center: Vec3) {
+        let offset = camera.eye - camera.center;
+        let distance = offset.magnitude();
+        let yaw = offset.z.atan2(offset.x);
+        let elevation = self.elevation.to_radians();
+
+        camera.center = center;
+        camera.eye = center + Vec3::new(
+            distance * elevation.cos() * yaw.cos(),
+            distance * elevation.sin(),
+            distance * elevation.cos() * yaw.sin(),
+        );
+    }
+
+    // Avanzar la cámara un cuadro usando la órbita de la cámara
+    pub fn step(&self, camera: &mut Camera) {
+        camera.orbit(self.step_angle(), 0.0);
+    }
+
+    // Luz para el cuadro `frame`: fija, o girada alrededor del eje Y que pasa por `center`
+    pub fn light_for_frame(&self, light: &Light, center: Vec3, frame: usize) -> Light {
+        if !self.rotate_light {
+            return light.clone();
+        }
+
+        let angle = self.step_angle() * frame as f32; // Mismo sentido que la órbita de la cámara
+        let offset = light.position - center;
+        let rotated = Vec3::new(
+            offset.x * angle.cos() - offset.z * angle.sin(),
+            offset.y,
+            offset.x * angle.sin() + offset.z * angle.cos(),
+        );
+        Light { position: center + rotated, ..light.clone() }
+    }
+}
+
+// Caja envolvente (mínimo, máximo) de los objetos, tomando cada uno como un cubo de lado `size`
+pub fn scene_bounds(objects: &[Box<dyn RayIntersect>], size: f32) -> (Vec3, Vec3) {
+    let half = Vec3::new(size / 2.0, size / 2.0, size / 2.0);
+    let mut min = Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+    let mut max = Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+
+    for object in objects {
+        let position = object.position();
+        min = min.inf(&(position - half));
+        max = max.sup(&(position + half));
+    }
+
+    if objects.is_empty() {
+        (Vec3::zeros(), Vec3::zeros())
+    } else {
+        (min, max)
+    }
+}